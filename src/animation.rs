@@ -0,0 +1,448 @@
+//! Sprite sheet animation for the player. Clip definitions (which atlas frames make up idle, run,
+//! jump, and so on, and how fast they play) live in a RON asset so artists can iterate without
+//! recompiling; on native builds the file hot-reloads like the campaign manifest.
+//!
+//! Each frame the player's `MovementState` and `Velocity` pick an `AnimationState`, the matching
+//! clip advances, and the sprite's `TextureAtlas` index and horizontal flip are updated. The atlas
+//! layout is a labeled sub-asset of the clip file, so both are freed together when the last handle
+//! drops.
+
+use std::collections::HashMap;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::ConditionalSendFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::movement::{MovementState, Stance, Velocity};
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Asset path of the player's clip definitions, relative to the `assets/` folder.
+pub const PLAYER_ANIMATIONS_PATH: &str = "textures/player.anim.ron";
+
+/// Registers the clip asset type/loader, queues the player's clip file, and schedules the
+/// animation systems after movement so they see this frame's state.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AnimationLibrary>()
+            .init_asset_loader::<AnimationLibraryLoader>()
+            .init_resource::<PlayerAnimations>()
+            .add_systems(
+                Update,
+                (attach_player_sprite_sheet, animate_player)
+                    .chain()
+                    .after(GameSet::Movement)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Animation states the player can be in. Used as keys in the clip file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Land,
+    WallSlide,
+    Hurt,
+    Crouch,
+    Crawl,
+    Attack,
+}
+
+/// Atlas frames and playback rate for one state.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AnimationClip {
+    pub frames: Vec<usize>,
+    pub fps: f32,
+    /// Non-looping clips hold their last frame and report `finished`.
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+    /// First and last frame (inclusive) during which an attack clip's hitbox is live.
+    #[serde(default)]
+    pub hit_frames: Option<(usize, usize)>,
+}
+
+impl AnimationClip {
+    /// Seconds to play every frame once.
+    pub fn duration(&self) -> f32 {
+        if self.fps > 0.0 {
+            self.frames.len() as f32 / self.fps
+        } else {
+            0.0
+        }
+    }
+
+    /// Start and end time, in seconds from the start of the clip, of the `hit_frames` window.
+    pub fn hit_window(&self) -> Option<(f32, f32)> {
+        let (first, last) = self.hit_frames?;
+        (self.fps > 0.0).then(|| (first as f32 / self.fps, (last + 1) as f32 / self.fps))
+    }
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+/// A loaded clip file: the sprite sheet, its grid layout, and one clip per state.
+#[derive(Asset, TypePath, Debug)]
+pub struct AnimationLibrary {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub clips: HashMap<AnimationState, AnimationClip>,
+}
+
+impl AnimationLibrary {
+    /// Clip for `state`, falling back to `Idle` for states the artist hasn't drawn yet. Use
+    /// `clips` directly when the fallback isn't wanted.
+    pub fn clip(&self, state: AnimationState) -> Option<&AnimationClip> {
+        self.clips
+            .get(&state)
+            .or_else(|| self.clips.get(&AnimationState::Idle))
+    }
+}
+
+/// Keeps the player's clip file alive for the lifetime of the app.
+#[derive(Resource)]
+pub struct PlayerAnimations(pub Handle<AnimationLibrary>);
+
+impl FromWorld for PlayerAnimations {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load(PLAYER_ANIMATIONS_PATH))
+    }
+}
+
+/// Per-entity playback state. `hurt` can be started by damage code and takes priority over
+/// movement-driven states until its clip finishes. `attacking` is set and cleared by the combat
+/// code, which times attacks from the same clip.
+#[derive(Component, Default)]
+pub struct AnimationPlayer {
+    pub state: AnimationState,
+    /// Index into the current clip's `frames`.
+    frame: usize,
+    timer: f32,
+    finished: bool,
+    facing_left: bool,
+    was_on_ground: bool,
+    hurt: bool,
+    attacking: bool,
+}
+
+impl AnimationPlayer {
+    /// Plays the hurt clip once, interrupting whatever was playing.
+    pub fn play_hurt(&mut self) {
+        self.hurt = true;
+        self.switch_to(AnimationState::Hurt);
+    }
+
+    /// Starts the attack clip from its first frame.
+    pub fn play_attack(&mut self) {
+        self.attacking = true;
+        self.state = AnimationState::Attack;
+        self.frame = 0;
+        self.timer = 0.0;
+        self.finished = false;
+    }
+
+    pub fn stop_attack(&mut self) {
+        self.attacking = false;
+    }
+
+    /// Restarts the current clip from its first frame, keeping the state and the hurt/attack
+    /// flags. Used when the clip file is reloaded and the frame index may no longer be valid.
+    fn restart_clip(&mut self) {
+        self.frame = 0;
+        self.timer = 0.0;
+    }
+
+    fn switch_to(&mut self, state: AnimationState) {
+        if self.state != state {
+            self.state = state;
+            self.frame = 0;
+            self.timer = 0.0;
+            self.finished = false;
+        }
+    }
+
+    /// Picks the state for this frame. One-shot clips (land, hurt) run to completion unless the
+    /// player starts moving or leaves the ground.
+    fn select_state(&self, movement: &MovementState, velocity: Vec2) -> AnimationState {
+        const RUN_THRESHOLD: f32 = 10.0;
+
+        if self.hurt {
+            return AnimationState::Hurt;
+        }
+
+        if self.attacking {
+            return AnimationState::Attack;
+        }
+
+        if !movement.on_ground {
+            let pressing_into_wall = movement.wall_contact != 0.0
+                && movement.axis.signum() == movement.wall_contact;
+            return if pressing_into_wall && velocity.y < 0.0 {
+                AnimationState::WallSlide
+            } else if velocity.y > 0.0 {
+                AnimationState::Jump
+            } else {
+                AnimationState::Fall
+            };
+        }
+
+        let running = velocity.x.abs() > RUN_THRESHOLD;
+        if movement.stance == Stance::Crouching {
+            return if running {
+                AnimationState::Crawl
+            } else {
+                AnimationState::Crouch
+            };
+        }
+
+        if !self.was_on_ground {
+            return if running {
+                AnimationState::Run
+            } else {
+                AnimationState::Land
+            };
+        }
+
+        if self.state == AnimationState::Land && !self.finished && !running {
+            return AnimationState::Land;
+        }
+
+        if running {
+            AnimationState::Run
+        } else {
+            AnimationState::Idle
+        }
+    }
+
+    /// Advances the current clip by `dt` seconds.
+    fn advance(&mut self, clip: &AnimationClip, dt: f32) {
+        if clip.frames.is_empty() || clip.fps <= 0.0 {
+            self.frame = 0;
+            return;
+        }
+
+        self.timer += dt;
+        let frame_time = 1.0 / clip.fps;
+        while self.timer >= frame_time {
+            self.timer -= frame_time;
+            if self.frame + 1 < clip.frames.len() {
+                self.frame += 1;
+            } else if clip.looping {
+                self.frame = 0;
+            } else {
+                self.finished = true;
+            }
+        }
+
+        self.frame = self.frame.min(clip.frames.len() - 1);
+    }
+}
+
+/// Swaps the player's placeholder texture for the sprite sheet once the clip file has loaded, and
+/// again whenever it is hot-reloaded. A reload keeps the player's animation state (an attack or
+/// hurt clip in progress carries on) and only restarts the current clip.
+#[allow(clippy::type_complexity)]
+fn attach_player_sprite_sheet(
+    mut commands: Commands,
+    animations: Res<PlayerAnimations>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut events: EventReader<AssetEvent<AnimationLibrary>>,
+    mut players: Query<(Entity, &mut Handle<Image>, Option<&mut AnimationPlayer>), With<Player>>,
+) {
+    let Some(library) = libraries.get(&animations.0) else {
+        return;
+    };
+    let reloaded = events
+        .read()
+        .any(|event| event.is_modified(animations.0.id()));
+
+    for (entity, mut texture, player) in &mut players {
+        match player {
+            Some(mut player) if reloaded => player.restart_clip(),
+            Some(_) => continue,
+            None => {
+                commands.entity(entity).insert(AnimationPlayer::default());
+            }
+        }
+
+        *texture = library.texture.clone();
+        commands.entity(entity).insert(TextureAtlas {
+            layout: library.layout.clone(),
+            index: 0,
+        });
+    }
+}
+
+/// Runs the state machine and writes the current frame and facing into the sprite.
+fn animate_player(
+    animations: Res<PlayerAnimations>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    time: Res<Time>,
+    mut players: Query<
+        (
+            &mut AnimationPlayer,
+            &mut TextureAtlas,
+            &mut Sprite,
+            &MovementState,
+            &Velocity,
+        ),
+        With<Player>,
+    >,
+) {
+    let Some(library) = libraries.get(&animations.0) else {
+        return;
+    };
+
+    for (mut player, mut atlas, mut sprite, movement, velocity) in &mut players {
+        if player.hurt && player.state == AnimationState::Hurt && player.finished {
+            player.hurt = false;
+        }
+
+        let state = player.select_state(movement, velocity.0);
+        player.switch_to(state);
+        player.was_on_ground = movement.on_ground;
+
+        if movement.axis != 0.0 {
+            player.facing_left = movement.axis < 0.0;
+        }
+        sprite.flip_x = player.facing_left;
+
+        if let Some(clip) = library.clip(player.state) {
+            player.advance(clip, time.delta_seconds());
+            if let Some(&index) = clip.frames.get(player.frame) {
+                atlas.index = index;
+            }
+        }
+    }
+}
+
+/// On-disk form of the clip file. The texture path is resolved into a handle (and the grid into an
+/// atlas layout) while loading.
+#[derive(Deserialize)]
+struct AnimationLibraryFile {
+    texture: String,
+    frame_size: (u32, u32),
+    columns: u32,
+    rows: u32,
+    clips: HashMap<AnimationState, AnimationClip>,
+}
+
+#[derive(Default)]
+struct AnimationLibraryLoader;
+
+#[derive(Debug, Error)]
+enum AnimationLibraryLoaderError {
+    #[error("encountered IO error reading animation clips: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unable to parse animation clips: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AnimationLibraryLoader {
+    type Asset = AnimationLibrary;
+    type Settings = ();
+    type Error = AnimationLibraryLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: AnimationLibraryFile = ron::de::from_bytes(&bytes)?;
+
+            let layout = TextureAtlasLayout::from_grid(
+                UVec2::new(file.frame_size.0, file.frame_size.1),
+                file.columns.max(1),
+                file.rows.max(1),
+                None,
+                None,
+            );
+
+            Ok(AnimationLibrary {
+                texture: load_context.load(file.texture),
+                layout: load_context.add_labeled_asset("layout".to_owned(), layout),
+                clips: file.clips,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(frames: usize, fps: f32, hit_frames: Option<(usize, usize)>) -> AnimationClip {
+        AnimationClip {
+            frames: (0..frames).collect(),
+            fps,
+            looping: false,
+            hit_frames,
+        }
+    }
+
+    #[test]
+    fn duration_covers_every_frame_once() {
+        assert_eq!(clip(6, 12.0, None).duration(), 0.5);
+        assert_eq!(clip(6, 0.0, None).duration(), 0.0);
+    }
+
+    #[test]
+    fn hit_window_spans_the_hit_frames() {
+        // Frames 2..=3 at 10 fps are drawn from 0.2 s until frame 4 starts at 0.4 s.
+        let (start, end) = clip(6, 10.0, Some((2, 3))).hit_window().unwrap();
+        assert!((start - 0.2).abs() < 1e-6 && (end - 0.4).abs() < 1e-6);
+
+        assert_eq!(clip(6, 10.0, None).hit_window(), None);
+        assert_eq!(clip(6, 0.0, Some((2, 3))).hit_window(), None);
+    }
+
+    #[test]
+    fn hit_window_matches_playback() {
+        let clip = clip(6, 10.0, Some((2, 3)));
+        let (start, end) = clip.hit_window().unwrap();
+        let mut player = AnimationPlayer::default();
+        player.play_attack();
+
+        // Sample each frame's midpoint: the hitbox is live exactly while a hit frame is drawn.
+        let mut elapsed = 0.0;
+        for step in [0.05, 0.1, 0.1, 0.1, 0.1, 0.1] {
+            player.advance(&clip, step);
+            elapsed += step;
+            let live = (start..end).contains(&elapsed);
+            let (first, last) = clip.hit_frames.unwrap();
+            assert_eq!(live, (first..=last).contains(&player.frame), "at {elapsed}s");
+        }
+    }
+
+    #[test]
+    fn restarting_a_clip_keeps_the_attack_going() {
+        let mut player = AnimationPlayer::default();
+        player.play_attack();
+        player.advance(&clip(6, 10.0, None), 0.35);
+        assert_eq!(player.frame, 3);
+
+        player.restart_clip();
+        assert_eq!(player.frame, 0);
+        assert_eq!(player.state, AnimationState::Attack);
+        assert!(player.attacking);
+    }
+}
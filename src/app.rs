@@ -7,12 +7,28 @@
 
 use bevy::prelude::*;
 
+use crate::animation::AnimationPlugin;
 use crate::audio::GameAudioPlugin;
+use crate::breakable::BreakablePlugin;
 use crate::camera::{CameraPlugin, FollowCamera};
+use crate::campaign::CampaignPlugin;
+use crate::cinematic::CinematicPlugin;
+use crate::collectible::CollectiblePlugin;
 use crate::collision::CollisionPlugin;
+use crate::combat::CombatPlugin;
+use crate::door::DoorPlugin;
+use crate::enemy::EnemyPlugin;
+use crate::health::HealthPlugin;
+use crate::interact::InteractPlugin;
 use crate::level::LevelPlugin;
+use crate::loading::LoadingPlugin;
 use crate::movement::MovementPlugin;
+use crate::navigation::NavigationPlugin;
 use crate::player::PlayerPlugin;
+use crate::projectile::ProjectilePlugin;
+use crate::pushable::PushablePlugin;
+use crate::puzzle::PuzzlePlugin;
+use crate::render::PixelPerfectPlugin;
 use crate::state::{toggle_pause, GameSet, GameState};
 use crate::transition::TransitionPlugin;
 use crate::ui::UiPlugin;
@@ -26,14 +42,33 @@ impl Plugin for DungeonPlatformerPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>() // Allocates the state machine in the ECS world.
             .add_plugins((
-                LevelPlugin,      // Level loading + LDtk asset plumbing.
-                PlayerPlugin,     // Player entity spawning logic.
-                GameAudioPlugin,  // Audio handle preloading.
-                CameraPlugin,     // Camera follow behaviour.
-                CollisionPlugin,  // Tile-based collision map.
-                MovementPlugin,   // Input + kinematic updates.
-                TransitionPlugin, // Level transitions with fade effects.
-                UiPlugin,         // Pause overlay.
+                CampaignPlugin,     // Campaign manifest asset + progress.
+                LevelPlugin,        // Level loading + LDtk asset plumbing.
+                LoadingPlugin,      // Asset progress tracking + loading screen.
+                PlayerPlugin,       // Player entity spawning logic.
+                AnimationPlugin,    // Player sprite sheet clips.
+                GameAudioPlugin,    // Audio handle preloading.
+                CameraPlugin,       // Camera follow behaviour.
+                CinematicPlugin,    // Scripted camera pans.
+                CollisionPlugin,    // Tile-based collision map.
+                MovementPlugin,     // Input + kinematic updates.
+            ))
+            // Plugin tuples are capped at 15 entries, so gameplay plugins get their own group.
+            .add_plugins((
+                HealthPlugin,       // Damage, invulnerability, and death.
+                CombatPlugin,       // Melee hitboxes, knockback, and hit-stop.
+                EnemyPlugin,        // LDtk-spawned patrolling enemies.
+                CollectiblePlugin,  // Coins, gems, hearts, keys + tally.
+                InteractPlugin,     // `E` presses routed to the nearest door or lever.
+                DoorPlugin,         // Locked doors opened with keys.
+                PuzzlePlugin,       // Levers, plates, timers, and logic wiring.
+                PushablePlugin,     // Crates that fall, stack, and get pushed.
+                BreakablePlugin,    // Breakable and crumbling tiles.
+                NavigationPlugin,   // Platform navigation graph + A*.
+                ProjectilePlugin,   // Pooled daggers, arrows, and trap darts.
+                TransitionPlugin,   // Level transitions with fade effects.
+                UiPlugin,           // Pause overlay.
+                PixelPerfectPlugin, // Integer-scaled low-resolution canvas.
             ))
             // Systems inside these sets execute sequentially while the game
             // is in the `Playing` state. `chain()` enforces Input → Movement
@@ -50,7 +85,7 @@ impl Plugin for DungeonPlatformerPlugin {
     }
 }
 
-/// Spawns the initial 2D camera tagged with `FollowCamera` so the follow system
+/// Spawns the initial 2D camera tagged with `FollowCamera` so the camera controller
 /// can locate it. The Bevy ECS automatically stores this entity in an archetype
 /// table; the camera components stay alive until the entity is despawned.
 fn setup_camera(mut commands: Commands) {
@@ -2,10 +2,13 @@
 //!
 //! Bevy's asset system reference-counts handles; when the last handle is dropped, the underlying
 //! audio buffer is released. The `AudioHandles` resource keeps optional handles alive until the
-//! user replaces them with real assets.
+//! user replaces them with real assets. Per-level music is named in the campaign manifest and
+//! started when gameplay begins.
 
 use bevy::prelude::*;
 
+use crate::campaign::Campaign;
+use crate::level::LevelConfig;
 use crate::state::GameState;
 
 /// Registers the audio loading system and allocates the persistent handle cache.
@@ -15,7 +18,8 @@ pub struct GameAudioPlugin;
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AudioHandles>()
-            .add_systems(OnEnter(GameState::Loading), load_audio_handles);
+            .add_systems(OnEnter(GameState::Loading), load_audio_handles)
+            .add_systems(OnEnter(GameState::Playing), play_level_music);
     }
 }
 
@@ -26,16 +30,67 @@ impl Plugin for GameAudioPlugin {
 pub struct AudioHandles {
     pub jump: Option<Handle<AudioSource>>,
     pub pickup: Option<Handle<AudioSource>>,
+    pub break_tile: Option<Handle<AudioSource>>,
     pub ambient: Option<Handle<AudioSource>>,
 }
 
+impl AudioHandles {
+    /// Iterates over every queued handle, e.g. so the loading screen can report their progress.
+    pub fn iter(&self) -> impl Iterator<Item = &Handle<AudioSource>> {
+        [&self.jump, &self.pickup, &self.break_tile, &self.ambient].into_iter().flatten()
+    }
+}
+
 /// Loads placeholder audio files using the global `AssetServer`. The server queues asynchronous
 /// asset fetches; once loaded, Bevy caches the decoded audio in memory and the handles in
 /// `AudioHandles` reference that cache. Until real files are provided, these act as no-ops.
 fn load_audio_handles(asset_server: Res<AssetServer>, mut handles: ResMut<AudioHandles>) {
     handles.jump = Some(asset_server.load("audio/jump.ogg"));
     handles.pickup = Some(asset_server.load("audio/pickup.ogg"));
+    handles.break_tile = Some(asset_server.load("audio/break.ogg"));
     handles.ambient = Some(asset_server.load("audio/ambient.ogg"));
 
     info!("Queued audio placeholders. Add actual files under assets/audio/ to enable playback.");
 }
+
+/// Marker for the looping music entity of the current level. Stores the asset path so re-entering
+/// `Playing` (e.g. after unpausing) doesn't restart a track that is already playing.
+#[derive(Component)]
+struct LevelMusic(String);
+
+/// Starts the track named by the current campaign level, replacing the previous one if it differs.
+/// Dropping the old entity stops its playback and releases the sink.
+fn play_level_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<LevelConfig>,
+    campaign: Campaign,
+    current: Query<(Entity, &LevelMusic)>,
+) {
+    let wanted = campaign
+        .manifest()
+        .and_then(|manifest| config.campaign_level(manifest))
+        .and_then(|level| level.music.clone());
+
+    if current
+        .iter()
+        .any(|(_, music)| Some(&music.0) == wanted.as_ref())
+    {
+        return;
+    }
+
+    for (entity, _) in &current {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if let Some(path) = wanted {
+        commands.spawn((
+            LevelMusic(path.clone()),
+            Name::new("LevelMusic"),
+            AudioBundle {
+                source: asset_server.load(path),
+                settings: PlaybackSettings::LOOP,
+            },
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! Destructible level tiles. IntGrid value 3 (`BREAKABLE_TILE`) is solid until an attack hits it;
+//! value 4 (`CRUMBLING_TILE`) starts shaking when the player stands on it, gives way a moment
+//! later, and comes back after a few seconds once nothing is in the way.
+//!
+//! Tiles are removed and restored one at a time: `CollisionMap::remove_solid` / `add_solid` plus a
+//! `CollisionMapChanged` for the navigation graph, and the rendered tile is hidden or shown through
+//! an index of tile entities built once per map rebuild. `TileBroken` and `TileRestored` report
+//! every change; this module uses them for debris (styled per `DestructibleKind`) and the break
+//! sound.
+//!
+//! Destructible tiles must be drawn by the IntGrid layer itself (e.g. with auto-layer rules) for
+//! them to disappear when broken. Broken tiles come back when the level is loaded again.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_tilemap::prelude::{TileColor, TileVisible};
+
+use crate::audio::AudioHandles;
+use crate::collision::{
+    boxes_overlap, rebuild_collision_map, CollisionMap, CollisionMapChanged, CollisionMapRebuilt,
+    CollisionSystems, BREAKABLE_TILE, CRUMBLING_TILE,
+};
+use crate::movement::{Collider, MovementSettings, MovementState};
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the strike/break events and the systems that break, crumble, and restore tiles.
+pub struct BreakablePlugin;
+
+impl Plugin for BreakablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DestructibleTiles>()
+            .add_event::<StrikeTile>()
+            .add_event::<TileBroken>()
+            .add_event::<TileRestored>()
+            .add_systems(
+                PostUpdate,
+                index_destructible_tiles
+                    .after(rebuild_collision_map)
+                    .in_set(CollisionSystems),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        strike_tiles,
+                        start_crumbling,
+                        update_crumbling,
+                        update_tile_visuals,
+                        spawn_debris,
+                    )
+                        .chain()
+                        .in_set(GameSet::Effects),
+                    update_debris,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Seconds a crumbling tile shakes before giving way.
+const CRUMBLE_DELAY: f32 = 0.5;
+
+/// Seconds a crumbled tile stays gone before trying to come back.
+const CRUMBLE_RESPAWN: f32 = 3.0;
+
+/// Debris pieces thrown out by a breaking tile, and how long they last.
+const DEBRIS_PIECES: usize = 6;
+const DEBRIS_LIFETIME: f32 = 0.6;
+
+/// Which kind of destructible tile changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DestructibleKind {
+    Breakable,
+    Crumbling,
+}
+
+impl DestructibleKind {
+    /// Debris colour: stone for breakable blocks, sand for crumbling ones.
+    fn debris_color(self) -> Color {
+        match self {
+            Self::Breakable => Color::srgb(0.55, 0.5, 0.45),
+            Self::Crumbling => Color::srgb(0.75, 0.65, 0.45),
+        }
+    }
+}
+
+/// Request to hit a tile with an attack. Only breakable tiles react.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StrikeTile {
+    pub tile: IVec2,
+}
+
+/// Sent when a tile stops being solid. `center` is the tile's world-space centre.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TileBroken {
+    pub tile: IVec2,
+    pub center: Vec2,
+    pub kind: DestructibleKind,
+}
+
+/// Sent when a crumbled tile comes back.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TileRestored {
+    pub tile: IVec2,
+}
+
+/// Where a crumbling tile is in its cycle, with the seconds left in that phase.
+#[derive(Clone, Copy, Debug)]
+enum Crumble {
+    Shaking(f32),
+    Gone(f32),
+}
+
+/// Rendered tile entities of every destructible tile, and the crumbling tiles currently active.
+#[derive(Resource, Default)]
+pub struct DestructibleTiles {
+    entities: HashMap<IVec2, Vec<Entity>>,
+    crumbling: HashMap<IVec2, Crumble>,
+}
+
+/// Rebuilds the tile entity index after the collision map is rebuilt. The level's tiles have just
+/// been respawned, so any crumbling in progress is forgotten too.
+fn index_destructible_tiles(
+    mut rebuilt: EventReader<CollisionMapRebuilt>,
+    cells: Query<(Entity, &GridCoords, &IntGridCell)>,
+    mut tiles: ResMut<DestructibleTiles>,
+) {
+    if rebuilt.read().count() == 0 {
+        return;
+    }
+
+    tiles.entities.clear();
+    tiles.crumbling.clear();
+    for (entity, coords, cell) in &cells {
+        if matches!(cell.value, BREAKABLE_TILE | CRUMBLING_TILE) {
+            tiles
+                .entities
+                .entry(IVec2::new(coords.x, coords.y))
+                .or_default()
+                .push(entity);
+        }
+    }
+}
+
+/// Breaks struck breakable tiles.
+fn strike_tiles(
+    mut strikes: EventReader<StrikeTile>,
+    mut map: ResMut<CollisionMap>,
+    mut broken: EventWriter<TileBroken>,
+    mut changed: EventWriter<CollisionMapChanged>,
+) {
+    let mut removed = Vec::new();
+    for StrikeTile { tile } in strikes.read() {
+        if map.get_tile_value(*tile) != Some(BREAKABLE_TILE) || !map.remove_solid(*tile) {
+            continue;
+        }
+        removed.push(*tile);
+        broken.send(TileBroken {
+            tile: *tile,
+            center: map.tile_to_world_rect(*tile).center(),
+            kind: DestructibleKind::Breakable,
+        });
+    }
+
+    if !removed.is_empty() {
+        changed.send(CollisionMapChanged { tiles: removed });
+    }
+}
+
+/// Starts the countdown on crumbling tiles the player is standing on.
+fn start_crumbling(
+    map: Res<CollisionMap>,
+    mut tiles: ResMut<DestructibleTiles>,
+    player_query: Query<(&Transform, &Collider, &MovementState), With<Player>>,
+) {
+    for (transform, collider, state) in &player_query {
+        if !state.on_ground {
+            continue;
+        }
+        // A thin box just under the feet.
+        let center = collider.center(transform.translation.truncate());
+        let below = Vec2::new(center.x, center.y - collider.half_extents.y - 1.0);
+        for tile in map.overlap_aabb(below, Vec2::new(collider.half_extents.x, 0.5)) {
+            if map.get_tile_value(tile) == Some(CRUMBLING_TILE) {
+                tiles
+                    .crumbling
+                    .entry(tile)
+                    .or_insert(Crumble::Shaking(CRUMBLE_DELAY));
+            }
+        }
+    }
+}
+
+/// Drops shaking tiles whose delay ran out and restores crumbled ones whose respawn time is up,
+/// waiting while a collider still overlaps the spot.
+fn update_crumbling(
+    time: Res<Time>,
+    mut tiles: ResMut<DestructibleTiles>,
+    mut map: ResMut<CollisionMap>,
+    bodies: Query<(&Transform, &Collider)>,
+    mut broken: EventWriter<TileBroken>,
+    mut restored: EventWriter<TileRestored>,
+    mut changed: EventWriter<CollisionMapChanged>,
+) {
+    let dt = time.delta_seconds();
+    let mut changed_tiles = Vec::new();
+
+    tiles.crumbling.retain(|tile, crumble| match crumble {
+        Crumble::Shaking(remaining) => {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                map.remove_solid(*tile);
+                changed_tiles.push(*tile);
+                broken.send(TileBroken {
+                    tile: *tile,
+                    center: map.tile_to_world_rect(*tile).center(),
+                    kind: DestructibleKind::Crumbling,
+                });
+                *crumble = Crumble::Gone(CRUMBLE_RESPAWN);
+            }
+            true
+        }
+        Crumble::Gone(remaining) => {
+            *remaining -= dt;
+            if *remaining > 0.0 {
+                return true;
+            }
+            let rect = map.tile_to_world_rect(*tile);
+            let occupied = bodies.iter().any(|(transform, collider)| {
+                let center = collider.center(transform.translation.truncate());
+                boxes_overlap(center, collider.half_extents, rect.center(), rect.half_size())
+            });
+            if occupied {
+                return true;
+            }
+            map.add_solid(*tile);
+            changed_tiles.push(*tile);
+            restored.send(TileRestored { tile: *tile });
+            false
+        }
+    });
+
+    if !changed_tiles.is_empty() {
+        changed.send(CollisionMapChanged {
+            tiles: changed_tiles,
+        });
+    }
+}
+
+/// Hides broken tiles, shows restored ones, and tints crumbling tiles while they shake.
+fn update_tile_visuals(
+    time: Res<Time>,
+    tiles: Res<DestructibleTiles>,
+    mut broken: EventReader<TileBroken>,
+    mut restored: EventReader<TileRestored>,
+    mut visuals: Query<(&mut TileVisible, &mut TileColor)>,
+) {
+    let entities = |tile: &IVec2| tiles.entities.get(tile).into_iter().flatten().copied();
+
+    for TileBroken { tile, .. } in broken.read() {
+        let mut iter = visuals.iter_many_mut(entities(tile));
+        while let Some((mut visible, mut color)) = iter.fetch_next() {
+            visible.0 = false;
+            color.0 = Color::WHITE;
+        }
+    }
+    for TileRestored { tile } in restored.read() {
+        let mut iter = visuals.iter_many_mut(entities(tile));
+        while let Some((mut visible, _)) = iter.fetch_next() {
+            visible.0 = true;
+        }
+    }
+
+    // Flicker between a warm tint and the normal colour while the tile shakes.
+    let flash = (time.elapsed_seconds() * 20.0).sin() > 0.0;
+    for (tile, crumble) in &tiles.crumbling {
+        if let Crumble::Shaking(_) = crumble {
+            let mut iter = visuals.iter_many_mut(entities(tile));
+            while let Some((_, mut color)) = iter.fetch_next() {
+                color.0 = if flash {
+                    Color::srgb(1.0, 0.7, 0.5)
+                } else {
+                    Color::WHITE
+                };
+            }
+        }
+    }
+}
+
+/// Short-lived chunk thrown out by a breaking tile.
+#[derive(Component)]
+struct Debris {
+    velocity: Vec2,
+    remaining: f32,
+}
+
+/// Throws out debris and plays the break sound for every broken tile.
+fn spawn_debris(
+    mut commands: Commands,
+    map: Res<CollisionMap>,
+    audio: Res<AudioHandles>,
+    mut broken: EventReader<TileBroken>,
+) {
+    for event in broken.read() {
+        let size = map.tile_size * 0.25;
+        for piece in 0..DEBRIS_PIECES {
+            // Spread evenly over a half-circle: struck blocks burst up and out, crumbling ones
+            // drop their pieces down and away.
+            let mut angle = std::f32::consts::PI * (piece as f32 + 0.5) / DEBRIS_PIECES as f32;
+            let mut speed = 90.0 + 30.0 * (piece % 3) as f32;
+            if event.kind == DestructibleKind::Crumbling {
+                angle += std::f32::consts::PI;
+                speed *= 0.5;
+            }
+            commands.spawn((
+                Name::new("Debris"),
+                Debris {
+                    velocity: Vec2::from_angle(angle) * speed,
+                    remaining: DEBRIS_LIFETIME,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: event.kind.debris_color(),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(event.center.extend(160.0)),
+                    ..default()
+                },
+            ));
+        }
+
+        if let Some(sound) = &audio.break_tile {
+            commands.spawn(AudioBundle {
+                source: sound.clone(),
+                settings: PlaybackSettings::DESPAWN,
+            });
+        }
+    }
+}
+
+fn update_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<MovementSettings>,
+    mut debris: Query<(Entity, &mut Debris, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut piece, mut transform) in &mut debris {
+        piece.remaining -= dt;
+        if piece.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        piece.velocity.y -= settings.gravity * dt;
+        transform.translation += (piece.velocity * dt).extend(0.0);
+    }
+}
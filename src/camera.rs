@@ -1,91 +1,617 @@
-//! Camera follow system. Keeps the main 2D camera centered on the player while respecting
-//! level boundaries. All transformations are managed through Bevy's ECS—no raw pointers or
-//! manual memory management required.
+//! Camera controller. Owns the main 2D camera's position and zoom: it follows the player while
+//! respecting level boundaries, or holds a room or scripted view depending on `CameraMode`. All
+//! transformations are managed through Bevy's ECS—no raw pointers or manual memory management
+//! required.
+//!
+//! Levels may contain `CameraZone` entities authored in LDtk. While the player stands inside a
+//! zone the camera is confined to that rectangle instead of the whole level, which lets one large
+//! level be split into separate rooms and corridors. Zones marked `Fixed` switch the follow mode to
+//! `CameraMode::FixedRoom`, holding the view on the room instead of tracking the player.
+//!
+//! Inside those bounds the camera tracks a focus point rather than the player directly (see
+//! `CameraFocus`): small movements inside a dead-zone are ignored, the view leads in the direction
+//! the player faces, and vertical re-centering waits until the player lands on a new platform.
+//!
+//! Any system can send a `CameraShake` event. Shakes add trauma that decays over their duration;
+//! the resulting offset is layered on top of the smoothed camera position, which is tracked
+//! separately from the transform so shaking (and texel snapping in pixel-perfect mode) never drags
+//! the camera off target.
 
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
+use bevy_ecs_ldtk::prelude::*;
 
-use crate::level::LevelAssets;
+use crate::level::{LevelAssets, LevelConfig};
+use crate::movement::MovementState;
 use crate::player::Player;
-use crate::state::GameSet;
+use crate::render::RenderSettings;
+use crate::state::{GameSet, GameState};
 
-/// Plugin that registers the camera-follow system. Bevy stores plugins on the heap owned by the
+/// Plugin that registers the camera controller. Bevy stores plugins on the heap owned by the
 /// app; once the app exits, resources are dropped automatically.
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            follow_player_camera
-                .after(GameSet::Movement) // run after movement so camera sees latest transform
-                .run_if(has_player_and_camera),
-        );
+        app.init_resource::<CameraSettings>()
+            .init_resource::<CameraController>()
+            .init_resource::<CameraBounds>()
+            .init_resource::<CameraFocus>()
+            .init_resource::<ScreenShake>()
+            .add_event::<CameraShake>()
+            .register_ldtk_entity::<CameraZoneBundle>("CameraZone")
+            .add_systems(OnEnter(GameState::Loading), reset_camera_tracking)
+            .add_systems(
+                Update,
+                (
+                    update_screen_shake.after(GameSet::Effects),
+                    update_camera
+                        .after(GameSet::Movement) // run after movement so camera sees latest transform
+                        .after(update_screen_shake)
+                        .in_set(CameraSystems)
+                        .run_if(has_camera),
+                ),
+            );
     }
 }
 
-/// Marker component so the follow system can locate the camera entity without relying on names.
+/// System set containing the camera controller, so systems that steer it (cinematic scripts) can
+/// run beforehand.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraSystems;
+
+/// Marker component so the controller can locate the camera entity without relying on names.
 #[derive(Component)]
 pub struct FollowCamera;
 
-/// Run condition that only schedules the follow system when both a player and camera exist. This
-/// prevents `get_single` panics during level transitions when entities may be missing.
-fn has_player_and_camera(
-    player_query: Query<Entity, With<Player>>,
-    camera_query: Query<Entity, With<FollowCamera>>,
-) -> bool {
-    !player_query.is_empty() && !camera_query.is_empty()
-}
-
-/// Smoothly interpolates the camera transform toward the player's location. The Lerped motion
-/// uses an exponential decay constant (`follow_speed`) to avoid overshooting while keeping the
-/// player centered. Orthographic scale is accounted for when clamping within level bounds so the
-/// camera never shows outside the playable area.
-fn follow_player_camera(
-    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<FollowCamera>>,
-    player_query: Query<&Transform, (With<Player>, Without<FollowCamera>)>,
+/// What drives the camera. Every mode goes through the same controller, so nothing else should
+/// write the camera transform or projection directly.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    /// Track the player through `CameraFocus`, confined to the active zone, at the level zoom.
+    #[default]
+    Follow,
+    /// Hold still at the centre of the zone the player is in (or the whole level).
+    FixedRoom,
+    /// Sit exactly at a scripted position and zoom, ignoring the player. Whoever pushes this mode
+    /// (see `cinematic.rs`) supplies the easing.
+    Cinematic { target: Vec2, zoom: f32 },
+}
+
+/// Stack of camera modes plus a pending snap. The top of the stack drives the camera; popping a
+/// temporary mode (e.g. a cinematic pan) hands control back to whatever was below it. The snap is
+/// requested on level entry so the first frame jumps straight to the target instead of sweeping
+/// across from the previous level.
+#[derive(Resource, Default)]
+pub struct CameraController {
+    /// Modes above the base `mode`; usually empty.
+    stack: Vec<CameraMode>,
+    /// Mode used when nothing has been pushed.
+    pub base: CameraMode,
+    snap: bool,
+    /// Smoothed camera centre before shake and texel snapping are applied.
+    position: Vec2,
+}
+
+impl CameraController {
+    /// Skips smoothing on the next update, moving and zooming the camera to its target at once.
+    pub fn snap(&mut self) {
+        self.snap = true;
+    }
+
+    /// The mode currently driving the camera.
+    pub fn mode(&self) -> CameraMode {
+        self.stack.last().copied().unwrap_or(self.base)
+    }
+
+    /// Layers a temporary mode on top of the current one.
+    pub fn push(&mut self, mode: CameraMode) {
+        self.stack.push(mode);
+    }
+
+    /// Removes the top temporary mode, returning control to the one below it.
+    pub fn pop(&mut self) -> Option<CameraMode> {
+        self.stack.pop()
+    }
+
+    /// Replaces the top temporary mode, or pushes one if the stack is empty. Scripts use this to
+    /// update their pose every frame.
+    pub fn replace_top(&mut self, mode: CameraMode) {
+        match self.stack.last_mut() {
+            Some(top) => *top = mode,
+            None => self.stack.push(mode),
+        }
+    }
+
+    /// Drops every temporary mode, e.g. when a level is unloaded mid-cinematic.
+    pub fn clear_stack(&mut self) {
+        self.stack.clear();
+    }
+
+    /// Smoothed camera centre, without shake or texel snapping.
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+}
+
+/// Camera feel tuning. Stored as a resource so it can be tweaked at runtime like
+/// `MovementSettings`. Speeds are exponential decay rates: higher values catch up faster.
+#[derive(Resource, Clone)]
+pub struct CameraSettings {
+    /// How quickly the camera moves toward its focus point.
+    pub follow_speed: f32,
+    /// Half size of the rectangle around the focus point the player can move in without
+    /// dragging the camera along.
+    pub dead_zone: Vec2,
+    /// Horizontal distance the focus leads the player in the direction they face.
+    pub look_ahead: f32,
+    /// How quickly the look-ahead offset swings over when the player turns around.
+    pub look_ahead_speed: f32,
+    /// How quickly the camera bounds blend toward a newly entered zone.
+    pub zone_blend_speed: f32,
+    /// Accessibility toggle; when off, `CameraShake` events are ignored.
+    pub screen_shake: bool,
+    /// Largest shake displacement, in world units, reached at full trauma.
+    pub shake_max_offset: f32,
+    /// How fast the shake offset wobbles, in radians per second.
+    pub shake_frequency: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            follow_speed: 6.0,
+            dead_zone: Vec2::new(24.0, 48.0),
+            look_ahead: 48.0,
+            look_ahead_speed: 3.0,
+            zone_blend_speed: 4.0,
+            screen_shake: true,
+            shake_max_offset: 12.0,
+            shake_frequency: 45.0,
+        }
+    }
+}
+
+/// Request to shake the camera. `intensity` is the trauma added (0–1, where 1 is the strongest
+/// shake) and fades to nothing over `duration` seconds.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CameraShake {
+    pub intensity: f32,
+    pub duration: f32,
+}
+
+/// Trauma-based shake state. Overlapping shakes add up, and the displacement grows with the square
+/// of the total trauma so light bumps stay subtle while big impacts hit hard.
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    shakes: Vec<ActiveShake>,
+    elapsed: f32,
+    /// Offset the controller should layer on this frame.
+    offset: Vec2,
+}
+
+struct ActiveShake {
+    intensity: f32,
+    duration: f32,
+    remaining: f32,
+}
+
+impl ScreenShake {
+    /// Current trauma in `0..=1`, summed across active shakes.
+    pub fn trauma(&self) -> f32 {
+        self.shakes
+            .iter()
+            .map(|shake| shake.intensity * shake.remaining / shake.duration)
+            .sum::<f32>()
+            .clamp(0.0, 1.0)
+    }
+
+    fn add(&mut self, shake: CameraShake) {
+        if shake.duration <= 0.0 || shake.intensity <= 0.0 {
+            return;
+        }
+
+        self.shakes.push(ActiveShake {
+            intensity: shake.intensity,
+            duration: shake.duration,
+            remaining: shake.duration,
+        });
+    }
+
+    /// Ages active shakes by `dt` and recomputes the offset from the remaining trauma.
+    fn advance(&mut self, settings: &CameraSettings, dt: f32) {
+        for shake in &mut self.shakes {
+            shake.remaining -= dt;
+        }
+        self.shakes.retain(|shake| shake.remaining > 0.0);
+
+        if self.shakes.is_empty() {
+            self.elapsed = 0.0;
+            self.offset = Vec2::ZERO;
+            return;
+        }
+
+        self.elapsed += dt;
+        let t = self.elapsed * settings.shake_frequency;
+        let wobble = Vec2::new(wobble(t, 0.0), wobble(t, 3.7));
+        self.offset = wobble * settings.shake_max_offset * self.trauma().powi(2);
+    }
+}
+
+/// Cheap smooth pseudo-noise in roughly `-1..=1`: a few incommensurate sines, phase-shifted per
+/// axis so X and Y don't move in lockstep.
+fn wobble(t: f32, phase: f32) -> f32 {
+    ((t + phase).sin() + 0.5 * (2.3 * t + 1.7 * phase).sin() + 0.25 * (5.1 * t + phase).sin())
+        / 1.75
+}
+
+/// The point the camera tries to centre on, derived from the player's path. Kept free of ECS types
+/// so it can be stepped frame by frame against a simulated sequence of player positions.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct CameraFocus {
+    /// `None` until the first update, and again after a reset, so the focus snaps to the player.
+    anchor: Option<Vec2>,
+    /// Height of the last platform the player stood on.
+    platform_y: f32,
+    /// -1.0 or 1.0 depending on the last horizontal input.
+    facing: f32,
+    /// Current (smoothed) horizontal look-ahead offset.
+    lead: f32,
+}
+
+impl CameraFocus {
+    /// Forgets all tracking state; the next `update` snaps to the player.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Advances the focus by `dt` seconds for a player at `player` and returns the point the
+    /// camera should centre on. `axis` is the horizontal input, used to determine facing.
+    pub fn update(
+        &mut self,
+        settings: &CameraSettings,
+        player: Vec2,
+        on_ground: bool,
+        axis: f32,
+        dt: f32,
+    ) -> Vec2 {
+        let mut anchor = match self.anchor {
+            Some(anchor) => anchor,
+            None => {
+                self.platform_y = player.y;
+                self.facing = 1.0;
+                player
+            }
+        };
+
+        if axis.abs() > f32::EPSILON {
+            self.facing = axis.signum();
+        }
+        let lead_t = 1.0 - f32::exp(-settings.look_ahead_speed * dt);
+        self.lead += (self.facing * settings.look_ahead - self.lead) * lead_t;
+
+        // Horizontally, the anchor is only dragged along once the player leaves the dead-zone.
+        anchor.x = anchor
+            .x
+            .clamp(player.x - settings.dead_zone.x, player.x + settings.dead_zone.x);
+
+        // Vertically, the anchor rests on the last platform height so jumps don't bob the view.
+        // Falls or climbs past the dead-zone still pull it along.
+        if on_ground {
+            self.platform_y = player.y;
+        }
+        anchor.y = self
+            .platform_y
+            .clamp(player.y - settings.dead_zone.y, player.y + settings.dead_zone.y);
+
+        self.anchor = Some(anchor);
+        Vec2::new(anchor.x + self.lead, anchor.y)
+    }
+}
+
+/// Rectangle the camera is confined to while the player is inside it. The entity's transform sits
+/// at the centre of the LDtk entity, so only the half extents need to be stored. `fixed` comes from
+/// the optional LDtk `Fixed` bool field.
+#[derive(Component, Clone, Copy, Default)]
+pub struct CameraZone {
+    pub half_extents: Vec2,
+    /// Hold the camera at the zone's centre instead of following the player.
+    pub fixed: bool,
+}
+
+impl CameraZone {
+    fn from_instance(instance: &EntityInstance) -> Self {
+        Self {
+            half_extents: Vec2::new(instance.width as f32, instance.height as f32) * 0.5,
+            fixed: instance.get_bool_field("Fixed").copied().unwrap_or(false),
+        }
+    }
+
+    /// World-space rectangle covered by the zone, given the entity's global transform.
+    pub fn rect(&self, transform: &GlobalTransform) -> Rect {
+        Rect::from_center_half_size(transform.translation().truncate(), self.half_extents)
+    }
+}
+
+/// Spawned by bevy_ecs_ldtk for every `CameraZone` entity instance in the project.
+#[derive(Bundle, LdtkEntity, Default)]
+struct CameraZoneBundle {
+    #[with(CameraZone::from_instance)]
+    zone: CameraZone,
+}
+
+/// The bounds the camera is currently clamped to. Blends toward the active zone (or the whole
+/// level) over a few frames rather than jumping when the player crosses a zone edge.
+#[derive(Resource, Default)]
+pub struct CameraBounds {
+    pub current: Option<Rect>,
+}
+
+/// Forgets the blended bounds and focus and requests a snap, so the first frame in a new level
+/// lands straight on the player and their zone.
+fn reset_camera_tracking(
+    mut controller: ResMut<CameraController>,
+    mut bounds: ResMut<CameraBounds>,
+    mut focus: ResMut<CameraFocus>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    controller.snap();
+    controller.clear_stack();
+    bounds.current = None;
+    focus.reset();
+    *shake = ScreenShake::default();
+}
+
+/// Collects shake requests and advances the trauma. With shake disabled, requests are dropped and
+/// any shake already running stops immediately.
+fn update_screen_shake(
+    mut events: EventReader<CameraShake>,
+    settings: Res<CameraSettings>,
+    mut shake: ResMut<ScreenShake>,
+    time: Res<Time>,
+) {
+    if !settings.screen_shake {
+        events.clear();
+        shake.shakes.clear();
+    } else {
+        for event in events.read() {
+            shake.add(*event);
+        }
+    }
+
+    shake.advance(&settings, time.delta_seconds());
+}
+
+/// Picks the rectangle the camera should be confined to: the smallest zone containing `point`, or
+/// the full level when no zone does. Also returns whether that zone is `fixed`; the level never is.
+fn active_bounds(
+    point: Vec2,
+    zones: &Query<(&CameraZone, &GlobalTransform)>,
+    level_assets: &LevelAssets,
+) -> Option<(Rect, bool)> {
+    let zone = zones
+        .iter()
+        .map(|(zone, transform)| (zone.rect(transform), zone.fixed))
+        .filter(|(rect, _)| rect.contains(point))
+        .min_by(|(a, _), (b, _)| {
+            a.size()
+                .element_product()
+                .total_cmp(&b.size().element_product())
+        });
+
+    zone.or_else(|| {
+        let (origin, size) = (level_assets.level_origin?, level_assets.level_size?);
+        Some((Rect::from_corners(origin, origin + size), false))
+    })
+}
+
+/// Clamps one axis of the camera centre so a view of `half_view` stays inside `min..max`. Bounds
+/// smaller than the view centre the camera on them instead.
+fn clamp_axis(value: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    if max - min > half_view * 2.0 {
+        value.clamp(min + half_view, max - half_view)
+    } else {
+        (min + max) * 0.5
+    }
+}
+
+/// Run condition that only schedules the camera controller when a camera exists. This prevents
+/// `get_single` panics during level transitions when entities may be missing.
+fn has_camera(camera_query: Query<Entity, With<FollowCamera>>) -> bool {
+    !camera_query.is_empty()
+}
+
+/// Moves and zooms the camera according to the current `CameraMode`. The Lerped motion uses an
+/// exponential decay constant (`follow_speed`) to avoid overshooting, except on the first frame
+/// after a level entry, which snaps. The visible area (window or pixel canvas) is accounted for
+/// when clamping within the active camera zone (or the level) so the camera never shows outside
+/// the playable area. Shake and texel snapping are applied last so they never feed back into the
+/// smoothing.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_camera(
+    mut camera_query: Query<
+        (&mut Transform, &mut OrthographicProjection, &Camera),
+        With<FollowCamera>,
+    >,
+    player_query: Query<(&Transform, &MovementState), (With<Player>, Without<FollowCamera>)>,
+    zones: Query<(&CameraZone, &GlobalTransform)>,
     level_assets: Res<LevelAssets>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    config: Res<LevelConfig>,
+    settings: Res<CameraSettings>,
+    mut controller: ResMut<CameraController>,
+    mut bounds: ResMut<CameraBounds>,
+    mut focus: ResMut<CameraFocus>,
+    shake: Res<ScreenShake>,
+    render: Res<RenderSettings>,
     time: Res<Time>,
 ) {
-    let Ok(player_transform) = player_query.get_single() else {
+    let Ok((mut camera_transform, mut projection, camera)) = camera_query.get_single_mut() else {
         return;
     };
 
-    let Ok((mut camera_transform, projection)) = camera_query.get_single_mut() else {
-        return;
-    };
+    let dt = time.delta_seconds();
+    let snap = controller.snap;
 
-    let target_z = camera_transform.translation.z;
-    let mut desired = Vec3::new(
-        player_transform.translation.x,
-        player_transform.translation.y,
-        target_z,
-    );
-
-    if let (Some(origin), Some(size)) = (level_assets.level_origin, level_assets.level_size) {
-        if let Ok(window) = window_query.get_single() {
-            let half_width = window.resolution.width() * 0.5 * projection.scale;
-            let half_height = window.resolution.height() * 0.5 * projection.scale;
-
-            let width_world = size.x;
-            let height_world = size.y;
-
-            if width_world > half_width * 2.0 {
-                let min_x = origin.x + half_width;
-                let max_x = origin.x + width_world - half_width;
-                desired.x = desired.x.clamp(min_x, max_x);
-            }
+    let mode = controller.mode();
+    let (target, zoom) = match mode {
+        CameraMode::Cinematic { target, zoom } => (target, zoom),
+        mode => {
+            // Room-based modes wait for the player so the snap lands on them, not the level
+            // centre.
+            let Ok((player_transform, movement)) = player_query.get_single() else {
+                return;
+            };
+            let player = player_transform.translation.truncate();
+            let (target_bounds, fixed) = active_bounds(player, &zones, &level_assets).unzip();
+            let mode = match mode {
+                CameraMode::Follow if fixed == Some(true) => CameraMode::FixedRoom,
+                mode => mode,
+            };
+
+            // Blend the bounds edges rather than the camera itself, so crossing into a zone eases
+            // the camera over while it keeps tracking the player.
+            let blend_t = 1.0 - f32::exp(-settings.zone_blend_speed * dt);
+            bounds.current = match (bounds.current, target_bounds) {
+                (Some(current), Some(target)) if !snap => Some(Rect {
+                    min: current.min.lerp(target.min, blend_t),
+                    max: current.max.lerp(target.max, blend_t),
+                }),
+                (_, target) => target,
+            };
+
+            let mut target = match mode {
+                CameraMode::FixedRoom => bounds.current.map_or(player, |rect| rect.center()),
+                _ => focus.update(&settings, player, movement.on_ground, movement.axis, dt),
+            };
+
+            if let (Some(rect), Some(viewport)) = (bounds.current, camera.logical_viewport_size())
+            {
+                let half_view = viewport * 0.5 * projection.scale;
 
-            if height_world > half_height * 2.0 {
-                let min_y = origin.y + half_height;
-                let max_y = origin.y + height_world - half_height;
-                desired.y = desired.y.clamp(min_y, max_y);
+                target.x = clamp_axis(target.x, rect.min.x, rect.max.x, half_view.x);
+                target.y = clamp_axis(target.y, rect.min.y, rect.max.y, half_view.y);
             }
+
+            (target, config.camera_zoom)
         }
+    };
+
+    // The pixel canvas already fixes the view size, so zoom stays at one texel per world unit.
+    let zoom = if render.pixel_perfect { 1.0 } else { zoom.max(0.0001) };
+    // Cinematic poses are already eased by their script; smoothing them again would lag behind.
+    let exact = snap || matches!(mode, CameraMode::Cinematic { .. });
+    let lerp_t = if exact {
+        1.0
+    } else {
+        1.0 - f32::exp(-settings.follow_speed * dt)
+    };
+    controller.position = controller.position.lerp(target, lerp_t);
+    projection.scale = if render.pixel_perfect {
+        zoom
+    } else {
+        projection.scale + (zoom - projection.scale) * lerp_t
+    };
+
+    let mut position = controller.position + shake.offset;
+    if render.pixel_perfect {
+        position = position.round();
+    }
+
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
+    controller.snap = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Steps the focus along `path` (player position, on ground, input axis) and returns the focus
+    /// point after every frame.
+    fn follow(
+        focus: &mut CameraFocus,
+        settings: &CameraSettings,
+        path: &[(Vec2, bool, f32)],
+    ) -> Vec<Vec2> {
+        path.iter()
+            .map(|&(player, on_ground, axis)| focus.update(settings, player, on_ground, axis, DT))
+            .collect()
     }
 
-    let follow_speed = 6.0;
-    let lerp_t = 1.0 - f32::exp(-follow_speed * time.delta_seconds());
-    camera_transform.translation = camera_transform.translation.lerp(desired, lerp_t);
+    #[test]
+    fn holds_still_inside_the_dead_zone() {
+        let settings = CameraSettings {
+            look_ahead: 0.0,
+            ..default()
+        };
+        let mut focus = CameraFocus::default();
+
+        // Shuffle around within the horizontal dead-zone (24 px either side).
+        let path: Vec<_> = [0.0, 10.0, 20.0, -15.0, 24.0]
+            .into_iter()
+            .map(|x| (Vec2::new(x, 0.0), true, 0.0))
+            .collect();
+        for point in follow(&mut focus, &settings, &path) {
+            assert_eq!(point.x, 0.0);
+        }
+
+        // Leaving it drags the focus along, one dead-zone width behind.
+        let point = focus.update(&settings, Vec2::new(40.0, 0.0), true, 1.0, DT);
+        assert_eq!(point.x, 16.0);
+    }
+
+    #[test]
+    fn look_ahead_swings_over_when_the_player_turns() {
+        let settings = CameraSettings::default();
+        let mut focus = CameraFocus::default();
+        let standing = |axis: f32| vec![(Vec2::ZERO, true, axis); 120];
+
+        let right = follow(&mut focus, &settings, &standing(1.0));
+        let lead = right.last().unwrap().x;
+        assert!((lead - settings.look_ahead).abs() < 1.0, "lead {lead}");
+
+        // Releasing the stick keeps the last facing.
+        let idle = follow(&mut focus, &settings, &standing(0.0));
+        assert!(idle.last().unwrap().x > settings.look_ahead - 1.0);
+
+        // Turning around eases the lead over to the other side instead of jumping.
+        let left = follow(&mut focus, &settings, &standing(-1.0));
+        assert!(left[0].x > 0.0);
+        assert!(left.windows(2).all(|pair| pair[1].x < pair[0].x));
+        let lead = left.last().unwrap().x;
+        assert!((lead + settings.look_ahead).abs() < 1.0, "lead {lead}");
+    }
+
+    #[test]
+    fn recentres_vertically_only_on_landing() {
+        let settings = CameraSettings {
+            look_ahead: 0.0,
+            ..default()
+        };
+        let mut focus = CameraFocus::default();
+        focus.update(&settings, Vec2::ZERO, true, 0.0, DT);
+
+        // A jump that stays within the vertical dead-zone (48 px) doesn't move the view.
+        let jump: Vec<_> = [10.0, 25.0, 40.0, 44.0]
+            .into_iter()
+            .map(|y| (Vec2::new(0.0, y), false, 0.0))
+            .collect();
+        for point in follow(&mut focus, &settings, &jump) {
+            assert_eq!(point.y, 0.0);
+        }
+
+        // Landing on a higher platform re-centres on it.
+        let point = focus.update(&settings, Vec2::new(0.0, 32.0), true, 0.0, DT);
+        assert_eq!(point.y, 32.0);
+
+        // Falling past the dead-zone pulls the view along while still airborne, but it only settles
+        // on the new height once the player lands.
+        let point = focus.update(&settings, Vec2::new(0.0, -40.0), false, 0.0, DT);
+        assert_eq!(point.y, 8.0);
+        let point = focus.update(&settings, Vec2::new(0.0, -40.0), false, 0.0, DT);
+        assert_eq!(point.y, 8.0);
+        let point = focus.update(&settings, Vec2::new(0.0, -40.0), true, 0.0, DT);
+        assert_eq!(point.y, -40.0);
+    }
 }
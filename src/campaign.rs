@@ -0,0 +1,259 @@
+//! Campaign manifest: a RON asset listing every level in play order, along with display names,
+//! music, spawn entries, and unlock requirements. `LevelConfig` resolves against it when a level
+//! is spawned, and the transition code asks it where each exit leads.
+//!
+//! The manifest is loaded through Bevy's asset system like any other file, so it benefits from
+//! hot reloading on native builds and HTTP fetching on the web build.
+
+use std::collections::HashSet;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadState};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::ConditionalSendFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Asset path of the manifest, relative to the `assets/` folder.
+pub const CAMPAIGN_PATH: &str = "levels/main.campaign.ron";
+
+/// Registers the manifest asset type/loader, queues the manifest load, and allocates the progress
+/// tracker used for unlock checks.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CampaignManifest>()
+            .init_asset_loader::<CampaignManifestLoader>()
+            .init_resource::<CampaignHandle>()
+            .init_resource::<CampaignProgress>();
+    }
+}
+
+/// Deserialized campaign definition. `start` names the level a fresh run begins in.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct CampaignManifest {
+    pub start: String,
+    pub levels: Vec<CampaignLevel>,
+}
+
+impl CampaignManifest {
+    /// Looks up a level by its manifest id.
+    pub fn level(&self, id: &str) -> Option<&CampaignLevel> {
+        self.levels.iter().find(|level| level.id == id)
+    }
+
+    /// The level a fresh run begins in.
+    pub fn start_level(&self) -> Option<&CampaignLevel> {
+        self.level(&self.start)
+    }
+
+    /// Finds the manifest entry describing an LDtk project (and optionally a level inside it).
+    /// Used when a configuration names a project path directly instead of a manifest id.
+    pub fn level_for_project(&self, project: &str, level: Option<&str>) -> Option<&CampaignLevel> {
+        self.levels.iter().find(|candidate| {
+            candidate.project == project
+                && (level.is_none() || level == Some(candidate.level.as_str()))
+        })
+    }
+}
+
+/// One playable level in the campaign.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CampaignLevel {
+    pub id: String,
+    pub display_name: String,
+    /// LDtk project path relative to `assets/`.
+    pub project: String,
+    /// Level identifier inside the LDtk project.
+    pub level: String,
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Named spawn points. The first entry is used when none is requested.
+    #[serde(default)]
+    pub entries: Vec<CampaignEntry>,
+    /// Manifest id of the level the exit trigger leads to.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Manifest ids that must be completed before this level can be entered.
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+impl CampaignLevel {
+    /// Spawn offset (from the level's bottom-left corner) for the named entry, falling back to the
+    /// first entry when the name is missing or unknown.
+    pub fn entry_position(&self, entry: Option<&str>) -> Option<Vec2> {
+        entry
+            .and_then(|name| self.entries.iter().find(|candidate| candidate.name == name))
+            .or_else(|| self.entries.first())
+            .map(|entry| Vec2::new(entry.position.0, entry.position.1))
+    }
+
+    /// True when every required level has been completed.
+    pub fn is_unlocked(&self, progress: &CampaignProgress) -> bool {
+        self.requires
+            .iter()
+            .all(|required| progress.completed.contains(required))
+    }
+}
+
+/// Named spawn point inside a level.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CampaignEntry {
+    pub name: String,
+    pub position: (f32, f32),
+}
+
+/// Keeps the manifest handle alive for the lifetime of the app.
+#[derive(Resource)]
+pub struct CampaignHandle(pub Handle<CampaignManifest>);
+
+impl FromWorld for CampaignHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load(CAMPAIGN_PATH))
+    }
+}
+
+/// Manifest ids of levels the player has exited through their trigger.
+#[derive(Resource, Default)]
+pub struct CampaignProgress {
+    pub completed: HashSet<String>,
+}
+
+/// Convenience parameter bundling the manifest handle with the asset storage so systems can fetch
+/// the manifest in one call.
+#[derive(SystemParam)]
+pub struct Campaign<'w> {
+    handle: Res<'w, CampaignHandle>,
+    manifests: Res<'w, Assets<CampaignManifest>>,
+}
+
+impl Campaign<'_> {
+    /// The loaded manifest, or `None` while it is loading or if it failed.
+    pub fn manifest(&self) -> Option<&CampaignManifest> {
+        self.manifests.get(&self.handle.0)
+    }
+
+    /// Id of the manifest asset, for load-state queries.
+    pub fn id(&self) -> AssetId<CampaignManifest> {
+        self.handle.0.id()
+    }
+}
+
+/// Run condition that passes once the manifest has either loaded or failed, so level spawning can
+/// proceed (falling back to a path-based `LevelConfig` if the manifest is missing).
+pub fn campaign_settled(asset_server: Res<AssetServer>, campaign: Campaign) -> bool {
+    matches!(
+        asset_server.get_load_state(campaign.id()),
+        Some(LoadState::Loaded | LoadState::Failed(_))
+    )
+}
+
+#[derive(Default)]
+struct CampaignManifestLoader;
+
+#[derive(Debug, Error)]
+enum CampaignManifestLoaderError {
+    #[error("encountered IO error reading campaign manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unable to parse campaign manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for CampaignManifestLoader {
+    type Asset = CampaignManifest;
+    type Settings = ();
+    type Error = CampaignManifestLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"(
+        start: "entrance",
+        levels: [
+            (
+                id: "entrance",
+                display_name: "Entrance",
+                project: "levels/a.ldtk",
+                level: "Level_0",
+                entries: [
+                    (name: "start", position: (10.0, 20.0)),
+                    (name: "back", position: (90.0, 20.0)),
+                ],
+                next: Some("cellar"),
+            ),
+            (
+                id: "cellar",
+                display_name: "Cellar",
+                project: "levels/a.ldtk",
+                level: "Level_1",
+                requires: ["entrance"],
+            ),
+        ],
+    )"#;
+
+    fn manifest() -> CampaignManifest {
+        ron::de::from_str(MANIFEST).unwrap()
+    }
+
+    #[test]
+    fn finds_levels_by_project_and_identifier() {
+        let manifest = manifest();
+        let id = |level: Option<&CampaignLevel>| level.map(|level| level.id.as_str());
+
+        assert_eq!(id(manifest.level_for_project("levels/a.ldtk", None)), Some("entrance"));
+        assert_eq!(
+            id(manifest.level_for_project("levels/a.ldtk", Some("Level_1"))),
+            Some("cellar")
+        );
+        assert_eq!(id(manifest.level_for_project("levels/a.ldtk", Some("Level_9"))), None);
+        assert_eq!(id(manifest.level_for_project("levels/b.ldtk", None)), None);
+    }
+
+    #[test]
+    fn next_levels_unlock_once_their_requirements_are_completed() {
+        let manifest = manifest();
+        let start = manifest.start_level().unwrap();
+        let next = manifest.level(start.next.as_deref().unwrap()).unwrap();
+        assert_eq!(next.id, "cellar");
+
+        let mut progress = CampaignProgress::default();
+        assert!(start.is_unlocked(&progress));
+        assert!(!next.is_unlocked(&progress));
+
+        progress.completed.insert(start.id.clone());
+        assert!(next.is_unlocked(&progress));
+        assert!(next.next.is_none());
+    }
+
+    #[test]
+    fn entry_positions_fall_back_to_the_first_entry() {
+        let manifest = manifest();
+        let start = manifest.start_level().unwrap();
+        assert_eq!(start.entry_position(Some("back")), Some(Vec2::new(90.0, 20.0)));
+        assert_eq!(start.entry_position(Some("roof")), Some(Vec2::new(10.0, 20.0)));
+        assert_eq!(start.entry_position(None), Some(Vec2::new(10.0, 20.0)));
+        assert_eq!(manifest.level("cellar").unwrap().entry_position(None), None);
+    }
+}
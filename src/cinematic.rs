@@ -0,0 +1,511 @@
+//! Scripted camera moves for boss reveals and puzzle hints. A `CameraScript` is a list of steps
+//! (pan, zoom, hold, return) played back one after another with easing. While a script plays, the
+//! `CameraDirector` keeps a `CameraMode::Cinematic` pose on top of the camera controller's mode
+//! stack and player input is suspended; when it finishes the mode is popped and the camera picks up
+//! following the player again.
+//!
+//! Any system can start a script by sending `PlayCameraScript`. Levels can also place
+//! `CameraTrigger` entities in LDtk, which play a reveal of their `Target` point when the player
+//! walks into them.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_ldtk::utils::{
+    ldtk_grid_coords_to_translation, ldtk_pixel_coords_to_translation_pivoted,
+};
+
+use crate::camera::{CameraController, CameraMode, CameraSystems, FollowCamera};
+use crate::collision::boxes_overlap;
+use crate::level::LevelConfig;
+use crate::movement::Collider;
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the script director, the trigger entity, and the playback systems.
+pub struct CinematicPlugin;
+
+impl Plugin for CinematicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraDirector>()
+            .add_event::<PlayCameraScript>()
+            .register_ldtk_entity::<CameraTriggerBundle>("CameraTrigger")
+            .add_systems(OnEnter(GameState::Loading), stop_camera_script)
+            .add_systems(
+                Update,
+                (
+                    fire_camera_triggers.in_set(GameSet::Effects),
+                    run_camera_script
+                        .after(GameSet::Effects)
+                        .before(CameraSystems)
+                        .run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
+
+/// Shapes how a step progresses over its duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress `t` in `0..=1` to eased progress (cubic curves).
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) * 0.5,
+        }
+    }
+}
+
+/// One segment of a camera script. Each step starts from wherever the previous one ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraStep {
+    /// Move the view centre to `to` (world coordinates).
+    Pan { to: Vec2, duration: f32, easing: Easing },
+    /// Change the zoom (orthographic scale) to `to` without moving.
+    Zoom { to: f32, duration: f32, easing: Easing },
+    /// Keep the current pose.
+    Hold { duration: f32 },
+    /// Move back to the player at the level's zoom.
+    Return { duration: f32, easing: Easing },
+}
+
+impl CameraStep {
+    fn duration(&self) -> f32 {
+        match *self {
+            CameraStep::Pan { duration, .. }
+            | CameraStep::Zoom { duration, .. }
+            | CameraStep::Hold { duration }
+            | CameraStep::Return { duration, .. } => duration.max(0.0),
+        }
+    }
+
+    /// Pose at linear progress `t`, given the pose the step started from and the player's pose.
+    fn pose_at(&self, from: CameraPose, home: CameraPose, t: f32) -> CameraPose {
+        match *self {
+            CameraStep::Pan { to, easing, .. } => CameraPose {
+                position: from.position.lerp(to, easing.apply(t)),
+                zoom: from.zoom,
+            },
+            CameraStep::Zoom { to, easing, .. } => CameraPose {
+                position: from.position,
+                zoom: from.zoom + (to - from.zoom) * easing.apply(t),
+            },
+            CameraStep::Hold { .. } => from,
+            CameraStep::Return { easing, .. } => {
+                let t = easing.apply(t);
+                CameraPose {
+                    position: from.position.lerp(home.position, t),
+                    zoom: from.zoom + (home.zoom - from.zoom) * t,
+                }
+            }
+        }
+    }
+}
+
+/// An ordered list of camera steps.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraScript {
+    pub steps: Vec<CameraStep>,
+}
+
+impl CameraScript {
+    /// Pans to `target`, optionally zooms, holds for `hold` seconds, then returns to the player,
+    /// easing every move with `easing`.
+    pub fn reveal(target: Vec2, zoom: Option<f32>, hold: f32, easing: Easing) -> Self {
+        let mut steps = vec![CameraStep::Pan {
+            to: target,
+            duration: 0.8,
+            easing,
+        }];
+        if let Some(zoom) = zoom {
+            steps.push(CameraStep::Zoom {
+                to: zoom,
+                duration: 0.5,
+                easing,
+            });
+        }
+        steps.push(CameraStep::Hold { duration: hold });
+        steps.push(CameraStep::Return {
+            duration: 0.8,
+            easing,
+        });
+
+        Self { steps }
+    }
+}
+
+/// Starts a camera script. Ignored while another script is already playing.
+#[derive(Event, Clone, Debug)]
+pub struct PlayCameraScript(pub CameraScript);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CameraPose {
+    position: Vec2,
+    zoom: f32,
+}
+
+struct PlayingScript {
+    steps: VecDeque<CameraStep>,
+    /// Time spent in the current (front) step.
+    elapsed: f32,
+    /// Pose the current step started from.
+    from: CameraPose,
+    pose: CameraPose,
+}
+
+/// Plays back camera scripts. Other systems check `is_playing` to hold off while the camera is
+/// busy, e.g. player input.
+#[derive(Resource, Default)]
+pub struct CameraDirector {
+    playing: Option<PlayingScript>,
+}
+
+impl CameraDirector {
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    /// Starts `script` from `pose`, layering a cinematic mode on top of the controller's stack.
+    /// Returns `false`, without starting it, while another script is playing.
+    fn start(
+        &mut self,
+        script: &CameraScript,
+        pose: CameraPose,
+        controller: &mut CameraController,
+    ) -> bool {
+        if self.is_playing() {
+            return false;
+        }
+
+        self.playing = Some(PlayingScript {
+            steps: script.steps.iter().copied().collect(),
+            elapsed: 0.0,
+            from: pose,
+            pose,
+        });
+        controller.push(CameraMode::Cinematic {
+            target: pose.position,
+            zoom: pose.zoom,
+        });
+        true
+    }
+
+    /// Advances the playing script by `dt` seconds and writes the resulting pose into the top of
+    /// the camera mode stack, popping that mode once the last step finishes. `Return` steps head
+    /// for `home`, or stay put when there is no player. Leftover frame time carries across step
+    /// boundaries so zero-length or very short steps don't each cost a frame.
+    fn advance(
+        &mut self,
+        controller: &mut CameraController,
+        home: Option<Vec2>,
+        zoom: f32,
+        mut dt: f32,
+    ) {
+        let Some(playing) = self.playing.as_mut() else {
+            return;
+        };
+
+        let home = CameraPose {
+            position: home.unwrap_or(playing.pose.position),
+            zoom,
+        };
+
+        while let Some(step) = playing.steps.front().copied() {
+            let duration = step.duration();
+            let advance = dt.min(duration - playing.elapsed).max(0.0);
+            playing.elapsed += advance;
+            dt -= advance;
+
+            let t = if duration > 0.0 {
+                playing.elapsed / duration
+            } else {
+                1.0
+            };
+            playing.pose = step.pose_at(playing.from, home, t);
+
+            if playing.elapsed < duration {
+                break;
+            }
+
+            playing.steps.pop_front();
+            playing.from = playing.pose;
+            playing.elapsed = 0.0;
+        }
+
+        if playing.steps.is_empty() {
+            self.playing = None;
+            controller.pop();
+        } else {
+            controller.replace_top(CameraMode::Cinematic {
+                target: playing.pose.position,
+                zoom: playing.pose.zoom,
+            });
+        }
+    }
+}
+
+/// Abandons any script when a level is unloaded. The camera controller clears its mode stack at the
+/// same time.
+fn stop_camera_script(mut director: ResMut<CameraDirector>) {
+    director.playing = None;
+}
+
+/// Starts requested scripts and advances the active one (see `CameraDirector::advance`).
+fn run_camera_script(
+    mut events: EventReader<PlayCameraScript>,
+    mut director: ResMut<CameraDirector>,
+    mut controller: ResMut<CameraController>,
+    camera_query: Query<&OrthographicProjection, With<FollowCamera>>,
+    player_query: Query<&Transform, With<Player>>,
+    config: Res<LevelConfig>,
+    time: Res<Time>,
+) {
+    for PlayCameraScript(script) in events.read() {
+        let pose = CameraPose {
+            position: controller.position(),
+            zoom: camera_query
+                .get_single()
+                .map_or(config.camera_zoom, |projection| projection.scale),
+        };
+        if !director.start(script, pose, &mut controller) {
+            info!("Camera script already playing; ignoring new request");
+        }
+    }
+
+    let home = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+    director.advance(&mut controller, home, config.camera_zoom, time.delta_seconds());
+}
+
+/// LDtk-authored area that plays a reveal of its `Target` point when the player walks in.
+///
+/// Fields: `Target` (Point, required), `Zoom` (Float, optional), `Hold` (Float, seconds, default
+/// 1.5), `Once` (Bool, default true) and `Easing` (Enum with values `Linear`, `EaseIn`, `EaseOut`,
+/// `EaseInOut`; default `EaseInOut`).
+#[derive(Component, Clone, Debug)]
+pub struct CameraTrigger {
+    half_extents: Vec2,
+    /// Target point relative to the trigger's centre.
+    target_offset: Vec2,
+    zoom: Option<f32>,
+    hold: f32,
+    easing: Easing,
+    once: bool,
+    fired: bool,
+    /// Whether the player overlapped the trigger last frame, so it fires on entry only.
+    occupied: bool,
+}
+
+#[derive(Bundle)]
+struct CameraTriggerBundle {
+    trigger: CameraTrigger,
+}
+
+impl LdtkEntity for CameraTriggerBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = IVec2::new(entity_instance.width, entity_instance.height);
+        let grid_size = layer_instance.grid_size;
+
+        // Both positions are relative to the level; only their difference is kept, because the
+        // trigger's world position is known from its transform once spawned.
+        let centre = ldtk_pixel_coords_to_translation_pivoted(
+            entity_instance.px,
+            layer_instance.c_hei * grid_size,
+            size,
+            entity_instance.pivot,
+        );
+        let target_offset = match entity_instance.get_point_field("Target") {
+            Ok(point) => {
+                ldtk_grid_coords_to_translation(
+                    *point,
+                    layer_instance.c_hei,
+                    IVec2::splat(grid_size),
+                ) - centre
+            }
+            Err(error) => {
+                warn!("CameraTrigger {} has no usable Target: {}", entity_instance.iid, error);
+                Vec2::ZERO
+            }
+        };
+
+        Self {
+            trigger: CameraTrigger {
+                half_extents: size.as_vec2() * 0.5,
+                target_offset,
+                zoom: entity_instance
+                    .get_maybe_float_field("Zoom")
+                    .ok()
+                    .copied()
+                    .flatten(),
+                hold: entity_instance
+                    .get_float_field("Hold")
+                    .copied()
+                    .unwrap_or(1.5),
+                easing: match entity_instance.get_enum_field("Easing").map(String::as_str) {
+                    Ok("Linear") => Easing::Linear,
+                    Ok("EaseIn") => Easing::EaseIn,
+                    Ok("EaseOut") => Easing::EaseOut,
+                    _ => Easing::EaseInOut,
+                },
+                once: entity_instance
+                    .get_bool_field("Once")
+                    .copied()
+                    .unwrap_or(true),
+                fired: false,
+                occupied: false,
+            },
+        }
+    }
+}
+
+/// Sends a reveal script when the player's collider enters a `CameraTrigger`.
+fn fire_camera_triggers(
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    mut triggers: Query<(&mut CameraTrigger, &GlobalTransform)>,
+    director: Res<CameraDirector>,
+    mut scripts: EventWriter<PlayCameraScript>,
+) {
+    let Ok((player_transform, collider)) = player_query.get_single() else {
+        return;
+    };
+    let player = collider.center(player_transform.translation.truncate());
+
+    for (mut trigger, transform) in &mut triggers {
+        let centre = transform.translation().truncate();
+        let overlapping =
+            boxes_overlap(player, collider.half_extents, centre, trigger.half_extents);
+        let entered = overlapping && !trigger.occupied;
+        trigger.occupied = overlapping;
+
+        if !entered || (trigger.once && trigger.fired) || director.is_playing() {
+            continue;
+        }
+
+        trigger.fired = true;
+        scripts.send(PlayCameraScript(CameraScript::reveal(
+            centre + trigger.target_offset,
+            trigger.zoom,
+            trigger.hold,
+            trigger.easing,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: CameraPose = CameraPose {
+        position: Vec2::ZERO,
+        zoom: 1.0,
+    };
+
+    fn top_pose(controller: &CameraController) -> Option<(Vec2, f32)> {
+        match controller.mode() {
+            CameraMode::Cinematic { target, zoom } => Some((target, zoom)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?}");
+            // Out-of-range progress is clamped.
+            assert_eq!(easing.apply(-1.0), 0.0, "{easing:?}");
+            assert!((easing.apply(2.0) - 1.0).abs() < 1e-6, "{easing:?}");
+        }
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn steps_play_in_order_and_carry_leftover_time() {
+        let script = CameraScript {
+            steps: vec![
+                CameraStep::Pan {
+                    to: Vec2::new(100.0, 0.0),
+                    duration: 1.0,
+                    easing: Easing::Linear,
+                },
+                CameraStep::Zoom {
+                    to: 2.0,
+                    duration: 0.0,
+                    easing: Easing::Linear,
+                },
+                CameraStep::Hold { duration: 1.0 },
+            ],
+        };
+        let mut director = CameraDirector::default();
+        let mut controller = CameraController::default();
+        assert!(director.start(&script, START, &mut controller));
+        assert!(!director.start(&script, START, &mut controller));
+
+        director.advance(&mut controller, None, 1.0, 0.5);
+        assert_eq!(top_pose(&controller), Some((Vec2::new(50.0, 0.0), 1.0)));
+
+        // Finishes the pan and the instant zoom, then spends the rest of the frame holding.
+        director.advance(&mut controller, None, 1.0, 0.75);
+        assert_eq!(top_pose(&controller), Some((Vec2::new(100.0, 0.0), 2.0)));
+        let playing = director.playing.as_ref().unwrap();
+        assert_eq!(playing.steps.len(), 1);
+        assert!((playing.elapsed - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn return_heads_home_and_pops_the_cinematic_mode() {
+        let script = CameraScript {
+            steps: vec![
+                CameraStep::Pan {
+                    to: Vec2::new(100.0, 0.0),
+                    duration: 0.0,
+                    easing: Easing::Linear,
+                },
+                CameraStep::Return {
+                    duration: 1.0,
+                    easing: Easing::Linear,
+                },
+            ],
+        };
+        let home = Some(Vec2::new(0.0, 40.0));
+        let mut director = CameraDirector::default();
+        let mut controller = CameraController::default();
+        director.start(&script, START, &mut controller);
+
+        director.advance(&mut controller, home, 3.0, 0.5);
+        assert_eq!(top_pose(&controller), Some((Vec2::new(50.0, 20.0), 2.0)));
+
+        director.advance(&mut controller, home, 3.0, 0.5);
+        assert!(!director.is_playing());
+        assert_eq!(controller.mode(), CameraMode::Follow);
+    }
+}
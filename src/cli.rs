@@ -0,0 +1,200 @@
+//! Command-line options for native builds. Lets developers jump straight into a specific room or
+//! toggle debug aids without editing `LevelConfig::default` and recompiling.
+//!
+//! Parsing is deliberately hand-rolled: the option set is small and flat, and every value is either
+//! a string or a `WIDTHxHEIGHT` pair.
+
+use bevy::prelude::*;
+use bevy::window::{WindowMode, WindowResolution};
+use thiserror::Error;
+
+use crate::level::LevelConfig;
+
+const USAGE: &str = "\
+Usage: dungeon_platformer [OPTIONS]
+
+Options:
+  --project <PATH>         LDtk project to load, relative to assets/
+  --level <NAME>           Campaign level id, or the LDtk level identifier when --project is given
+  --entry <NAME>           Named spawn entry inside the level
+  --fullscreen             Start in borderless fullscreen
+  --window-size <WxH>      Initial window size, e.g. 1920x1080
+  --mute                   Start with the global volume at zero
+  --debug-collision        Draw collision tiles, colliders, navigation links and sight lines
+  --no-screen-shake        Disable camera shake effects
+  --pixel-perfect          Render to a low-resolution canvas scaled by whole numbers
+  -h, --help               Print this help";
+
+/// Options collected from `std::env::args`. Everything is optional; absent values leave the
+/// corresponding defaults untouched.
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    pub project: Option<String>,
+    pub level: Option<String>,
+    pub entry: Option<String>,
+    pub fullscreen: bool,
+    pub window_size: Option<Vec2>,
+    pub mute: bool,
+    pub debug_collision: bool,
+    pub no_screen_shake: bool,
+    pub pixel_perfect: bool,
+    /// `-h`/`--help` was given; the rest of the command line is ignored.
+    pub help: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("missing value for '{0}'")]
+    MissingValue(String),
+    #[error("invalid window size '{0}', expected WIDTHxHEIGHT")]
+    InvalidWindowSize(String),
+    #[error("unknown argument '{0}'")]
+    UnknownArgument(String),
+}
+
+impl CliOptions {
+    /// Parses the process arguments. Prints usage and exits on `--help` or on a parse error, since
+    /// there is no sensible way to continue with a half-understood command line.
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(options) if options.help => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Ok(options) => options,
+            Err(error) => {
+                eprintln!("error: {error}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// Parses an argument list (without the program name). A help flag anywhere wins over every
+    /// other argument, including malformed ones.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let args: Vec<String> = args.into_iter().collect();
+        if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            return Ok(Self {
+                help: true,
+                ..default()
+            });
+        }
+
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| CliError::MissingValue(arg.clone()));
+
+            match arg.as_str() {
+                "--project" => options.project = Some(value()?),
+                "--level" => options.level = Some(value()?),
+                "--entry" => options.entry = Some(value()?),
+                "--window-size" => {
+                    let size = value()?;
+                    options.window_size =
+                        Some(parse_window_size(&size).ok_or(CliError::InvalidWindowSize(size))?);
+                }
+                "--fullscreen" => options.fullscreen = true,
+                "--mute" => options.mute = true,
+                "--debug-collision" => options.debug_collision = true,
+                "--no-screen-shake" => options.no_screen_shake = true,
+                "--pixel-perfect" => options.pixel_perfect = true,
+                _ => return Err(CliError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Overrides the level selection. `--project` bypasses the campaign lookup by id, in which case
+    /// `--level` names the LDtk level inside that project; otherwise `--level` is a campaign id.
+    pub fn apply_to_level_config(&self, config: &mut LevelConfig) {
+        if let Some(project) = &self.project {
+            config.level_id = None;
+            config.project_path = project.clone();
+            config.start_level = self.level.clone();
+        } else if let Some(level) = &self.level {
+            config.level_id = Some(level.clone());
+        }
+
+        if self.entry.is_some() {
+            config.entry = self.entry.clone();
+        }
+    }
+
+    /// Applies window size and fullscreen overrides to the primary window description.
+    pub fn apply_to_window(&self, window: &mut Window) {
+        if let Some(size) = self.window_size {
+            window.resolution = WindowResolution::new(size.x, size.y);
+        }
+
+        if self.fullscreen {
+            window.mode = WindowMode::BorderlessFullscreen;
+        }
+    }
+}
+
+fn parse_window_size(value: &str) -> Option<Vec2> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    let width: f32 = width.trim().parse().ok()?;
+    let height: f32 = height.trim().parse().ok()?;
+    (width > 0.0 && height > 0.0).then(|| Vec2::new(width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+        CliOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_values_and_flags() {
+        let options = parse(&["--level", "crypt", "--window-size", "1280x720", "--mute"]).unwrap();
+        assert_eq!(options.level.as_deref(), Some("crypt"));
+        assert_eq!(options.window_size, Some(Vec2::new(1280.0, 720.0)));
+        assert!(options.mute);
+        assert!(!options.help);
+    }
+
+    #[test]
+    fn missing_value_names_the_option() {
+        match parse(&["--mute", "--project"]) {
+            Err(CliError::MissingValue(option)) => assert_eq!(option, "--project"),
+            other => panic!("expected a missing value error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_window_sizes() {
+        for size in ["1280", "1280x", "x720", "widexhigh", "0x720", "-1280x720"] {
+            match parse(&["--window-size", size]) {
+                Err(CliError::InvalidWindowSize(value)) => assert_eq!(value, size),
+                other => panic!("expected '{size}' to be rejected, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        match parse(&["--fullscreen", "--fly"]) {
+            Err(CliError::UnknownArgument(arg)) => assert_eq!(arg, "--fly"),
+            other => panic!("expected an unknown argument error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn help_wins_over_everything_else() {
+        for args in [
+            &["--help"][..],
+            &["--fly", "-h"],
+            &["--window-size", "big", "--help"],
+        ] {
+            let options = parse(args).unwrap();
+            assert!(options.help);
+            assert!(options.level.is_none());
+        }
+    }
+}
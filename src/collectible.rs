@@ -0,0 +1,283 @@
+//! Coins, gems, and hearts placed as `Collectible` entities in LDtk, and coloured `Key` entities.
+//! Touching one with the player's collider picks it up: the global `CollectibleTally` goes up,
+//! hearts also restore health, and the pickup sound plays. Each pickup is reported with
+//! `ItemCollected`, which the HUD uses to name it on screen. Keys stay in the tally until a locked
+//! door (see `door`) takes them out again.
+//!
+//! Picked-up items are remembered per level (by LDtk IID) in `CollectedItems`, which survives
+//! `GameState::Loading`, so items stay gone when the player leaves a room and comes back or the
+//! level is reloaded after a death.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::audio::AudioHandles;
+use crate::collision::boxes_overlap;
+use crate::health::Health;
+use crate::level::{LevelAssets, LevelIids};
+use crate::movement::Collider;
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the collectible and key LDtk entities, the persistent collected set, and the tally.
+pub struct CollectiblePlugin;
+
+impl Plugin for CollectiblePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollectedItems>()
+            .init_resource::<CollectibleTally>()
+            .add_event::<ItemCollected>()
+            .register_ldtk_entity::<CollectibleBundle>("Collectible")
+            .register_ldtk_entity::<CollectibleBundle>("Key")
+            .add_systems(
+                Update,
+                (
+                    remove_collected_items.before(GameSet::Input),
+                    collect_items.in_set(GameSet::Effects),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// What a collectible is. Set with the LDtk `Kind` enum field, or the `Color` field of a `Key`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CollectibleKind {
+    #[default]
+    Coin,
+    Gem,
+    /// Restores `value` health as well as counting towards the tally.
+    Heart,
+    /// Opens one locked door of the same colour.
+    Key(KeyColor),
+}
+
+impl CollectibleKind {
+    /// Display name for `count` of this kind, e.g. "Coins" or "Red Key".
+    pub fn name(self, count: u32) -> String {
+        let (one, many) = match self {
+            CollectibleKind::Coin => ("Coin", "Coins"),
+            CollectibleKind::Gem => ("Gem", "Gems"),
+            CollectibleKind::Heart => ("Heart", "Hearts"),
+            CollectibleKind::Key(color) => {
+                let noun = if count == 1 { "Key" } else { "Keys" };
+                return format!("{:?} {}", color, noun);
+            }
+        };
+        let name = if count == 1 { one } else { many };
+        name.to_string()
+    }
+
+    fn color(self) -> Color {
+        match self {
+            CollectibleKind::Coin => Color::srgb(1.0, 0.85, 0.3),
+            CollectibleKind::Gem => Color::srgb(0.4, 0.85, 1.0),
+            CollectibleKind::Heart => Color::srgb(1.0, 0.35, 0.45),
+            CollectibleKind::Key(color) => color.color(),
+        }
+    }
+}
+
+/// Which doors a key opens. Set with the LDtk `Color` enum field on keys and locked doors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    #[default]
+    Red,
+    Blue,
+    Green,
+    Yellow,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 4] = [
+        KeyColor::Red,
+        KeyColor::Blue,
+        KeyColor::Green,
+        KeyColor::Yellow,
+    ];
+
+    pub fn from_field(entity_instance: &EntityInstance) -> Self {
+        match entity_instance.get_enum_field("Color").map(String::as_str) {
+            Ok("Blue") => KeyColor::Blue,
+            Ok("Green") => KeyColor::Green,
+            Ok("Yellow") => KeyColor::Yellow,
+            _ => KeyColor::Red,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            KeyColor::Red => Color::srgb(0.9, 0.25, 0.2),
+            KeyColor::Blue => Color::srgb(0.25, 0.45, 0.95),
+            KeyColor::Green => Color::srgb(0.3, 0.8, 0.35),
+            KeyColor::Yellow => Color::srgb(0.95, 0.8, 0.2),
+        }
+    }
+}
+
+/// LDtk-authored pickup.
+///
+/// `Collectible` fields: `Kind` (Enum with values `Coin`, `Gem`, `Heart`; default `Coin`) and
+/// `Value` (Int, default 1). `Key` fields: `Color` (Enum with values `Red`, `Blue`, `Green`,
+/// `Yellow`); a key is always worth one.
+#[derive(Component, Clone, Debug)]
+pub struct Collectible {
+    pub kind: CollectibleKind,
+    pub value: u32,
+    iid: String,
+    half_extents: Vec2,
+}
+
+#[derive(Bundle)]
+struct CollectibleBundle {
+    collectible: Collectible,
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for CollectibleBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+        let (kind, value, scale) = if entity_instance.identifier == "Key" {
+            let kind = CollectibleKind::Key(KeyColor::from_field(entity_instance));
+            (kind, 1, Vec2::new(0.5, 0.75))
+        } else {
+            let kind = match entity_instance.get_enum_field("Kind").map(String::as_str) {
+                Ok("Gem") => CollectibleKind::Gem,
+                Ok("Heart") => CollectibleKind::Heart,
+                _ => CollectibleKind::Coin,
+            };
+            let value = entity_instance
+                .get_int_field("Value")
+                .copied()
+                .unwrap_or(1)
+                .max(0) as u32;
+            (kind, value, Vec2::splat(0.75))
+        };
+
+        Self {
+            collectible: Collectible {
+                kind,
+                value,
+                iid: entity_instance.iid.clone(),
+                half_extents: size * 0.5,
+            },
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(size * scale),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// IIDs of picked-up collectibles, keyed by the IID of the level they were in.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct CollectedItems(pub LevelIids);
+
+/// What the player is carrying: everything picked up this session, less the keys used on doors.
+#[derive(Resource, Default, Debug)]
+pub struct CollectibleTally {
+    pub counts: HashMap<CollectibleKind, u32>,
+}
+
+impl CollectibleTally {
+    pub fn count(&self, kind: CollectibleKind) -> u32 {
+        self.counts.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// Uses up one of `kind`, returning whether there was one.
+    pub fn take(&mut self, kind: CollectibleKind) -> bool {
+        match self.counts.get_mut(&kind) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Sent whenever the player picks something up.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ItemCollected {
+    pub kind: CollectibleKind,
+    pub value: u32,
+}
+
+/// Despawns collectibles the player already picked up on an earlier visit as soon as the level
+/// (re)spawns them.
+fn remove_collected_items(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    collected: Res<CollectedItems>,
+    items: Query<(Entity, &Collectible), Added<Collectible>>,
+) {
+    let Some(level_iid) = level_assets.level_iid.as_deref() else {
+        return;
+    };
+
+    for (entity, item) in &items {
+        if collected.contains(level_iid, &item.iid) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Picks up collectibles overlapping the player's collider.
+#[allow(clippy::too_many_arguments)]
+fn collect_items(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    audio: Res<AudioHandles>,
+    mut collected: ResMut<CollectedItems>,
+    mut tally: ResMut<CollectibleTally>,
+    mut events: EventWriter<ItemCollected>,
+    mut player_query: Query<(&Transform, &Collider, Option<&mut Health>), With<Player>>,
+    items: Query<(Entity, &Collectible, &GlobalTransform)>,
+) {
+    let Ok((player_transform, collider, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some(level_iid) = level_assets.level_iid.as_deref() else {
+        return;
+    };
+    let player = collider.center(player_transform.translation.truncate());
+
+    for (entity, item, transform) in &items {
+        let center = transform.translation().truncate();
+        if !boxes_overlap(player, collider.half_extents, center, item.half_extents) {
+            continue;
+        }
+
+        collected.insert(level_iid, &item.iid);
+        *tally.counts.entry(item.kind).or_default() += item.value;
+
+        if let (CollectibleKind::Heart, Some(health)) = (item.kind, health.as_mut()) {
+            health.heal(item.value as i32);
+        }
+        if let Some(pickup) = &audio.pickup {
+            commands.spawn(AudioBundle {
+                source: pickup.clone(),
+                settings: PlaybackSettings::DESPAWN,
+            });
+        }
+
+        events.send(ItemCollected {
+            kind: item.kind,
+            value: item.value,
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -2,13 +2,14 @@
 //! the movement system queries. The data lives in a Bevy resource so it can be accessed by any
 //! system without copying large structures.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use bevy::math::IVec2;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
 use crate::level::{LevelAssets, LevelConfig};
+use crate::movement::Collider;
 
 /// Registers the collision map resource and rebuild system. Bevy keeps plugin state in its ECS
 /// world; no manual allocation or freeing is necessary.
@@ -16,12 +17,20 @@ pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CollisionMap>().add_systems(
-            PostUpdate,
-            rebuild_collision_map
-                .after(crate::level::sync_level_spatial)
-                .in_set(CollisionSystems),
-        );
+        app.init_resource::<CollisionMap>()
+            .init_resource::<CollisionDebug>()
+            .add_event::<CollisionMapRebuilt>()
+            .add_event::<CollisionMapChanged>()
+            .add_systems(
+                PostUpdate,
+                rebuild_collision_map
+                    .after(crate::level::cache_level_transform)
+                    .in_set(CollisionSystems),
+            )
+            .add_systems(
+                Update,
+                draw_collision_debug.run_if(collision_debug_enabled),
+            );
     }
 }
 
@@ -29,44 +38,396 @@ impl Plugin for CollisionPlugin {
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CollisionSystems;
 
-/// Runtime collision data. Stores the LDtk tile size, world origin, and a hash-set of solid cell
-/// coordinates. The hash-set grants O(1) `is_solid` queries while remaining compact in memory.
+/// Sent after `rebuild_collision_map` repopulates the map, so systems holding positions that were
+/// valid against the old geometry (e.g. the player after a hot reload) can re-validate them.
+#[derive(Event)]
+pub struct CollisionMapRebuilt;
+
+/// Sent when gameplay adds or removes solid tiles between rebuilds (doors opening, blocks
+/// breaking), listing the tiles that changed.
+#[derive(Event, Clone, Debug)]
+pub struct CollisionMapChanged {
+    pub tiles: Vec<IVec2>,
+}
+
+/// Toggles gizmo outlines for collision tiles and colliders (`--debug-collision` on native).
+#[derive(Resource, Default)]
+pub struct CollisionDebug {
+    pub enabled: bool,
+}
+
+/// IntGrid value of solid tiles that attacks break for good.
+pub const BREAKABLE_TILE: i32 = 3;
+
+/// IntGrid value of solid tiles that crumble shortly after being stood on and come back later.
+pub const CRUMBLING_TILE: i32 = 4;
+
+/// Tolerance used by shape queries so that boxes resting exactly on a tile edge don't count as
+/// overlapping it.
+const EDGE: f32 = 0.001;
+
+/// First solid tile hit by `CollisionMap::raycast`. `normal` points out of the face that was hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub tile: IVec2,
+    pub point: Vec2,
+    pub normal: Vec2,
+}
+
+/// First solid tile hit by `CollisionMap::sweep_aabb`. `time` is the fraction of the motion
+/// completed before contact and `position` the box centre at that moment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    pub tile: IVec2,
+    pub time: f32,
+    pub position: Vec2,
+    pub normal: Vec2,
+}
+
+/// Runtime collision data. Stores the LDtk tile size, world origin, and hash-sets of solid cell
+/// coordinates. The hash-sets grant O(1) `is_solid` queries while remaining compact in memory.
+///
+/// Solid tiles come in two layers: `solids` holds the level's own geometry and `blockers` the tiles
+/// closed at runtime by doors and gates. Blockers are counted, so a tile stays solid until every
+/// blocker covering it is removed, and removing one never clears the level geometry beneath it.
 #[derive(Resource, Default)]
 pub struct CollisionMap {
     pub tile_size: Vec2,
     pub origin: Vec2,
     pub solids: HashSet<IVec2>,
-    pub tile_values: std::collections::HashMap<IVec2, i32>,
+    pub tile_values: HashMap<IVec2, i32>,
+    /// Runtime blockers and how many cover each tile. Change with `add_blocker` / `remove_blocker`.
+    pub blockers: HashMap<IVec2, u32>,
 }
 
 impl CollisionMap {
-    /// Clears the hash-set. Memory is retained by the `HashSet` allocation for reuse in the next
-    /// rebuild, avoiding repeated heap allocations.
+    /// Clears both layers. Memory is retained by the allocations for reuse in the next rebuild,
+    /// avoiding repeated heap allocations.
     pub fn clear(&mut self) {
         self.solids.clear();
         self.tile_values.clear();
+        self.blockers.clear();
+    }
+
+    /// Returns whether the map has no solid tiles in either layer.
+    pub fn is_empty(&self) -> bool {
+        self.solids.is_empty() && self.blockers.is_empty()
     }
 
     /// Returns whether the given tile coordinate is flagged as solid.
     pub fn is_solid(&self, tile: IVec2) -> bool {
-        self.solids.contains(&tile)
+        self.solids.contains(&tile) || self.blockers.contains_key(&tile)
+    }
+
+    /// Every solid tile. A tile in both layers is listed twice.
+    pub fn solid_tiles(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.solids.iter().chain(self.blockers.keys()).copied()
+    }
+
+    /// Adds a tile to the level geometry at runtime, e.g. a crumbled block coming back. Returns
+    /// whether it was previously missing.
+    pub fn add_solid(&mut self, tile: IVec2) -> bool {
+        self.tile_values.entry(tile).or_insert(1);
+        self.solids.insert(tile)
+    }
+
+    /// Removes a tile from the level geometry at runtime. Returns whether it was there. Blockers
+    /// on the same tile keep it solid.
+    pub fn remove_solid(&mut self, tile: IVec2) -> bool {
+        if self.tile_values.get(&tile) == Some(&1) {
+            self.tile_values.remove(&tile);
+        }
+        self.solids.remove(&tile)
+    }
+
+    /// Blocks a tile at runtime, e.g. for a closed door. Returns whether it was previously free.
+    /// The next `rebuild_collision_map` discards blockers, so their owners re-apply them after each
+    /// rebuild.
+    pub fn add_blocker(&mut self, tile: IVec2) -> bool {
+        let was_solid = self.is_solid(tile);
+        *self.blockers.entry(tile).or_default() += 1;
+        !was_solid
+    }
+
+    /// Removes one blocker added with `add_blocker`. Returns whether the tile became free, i.e. it
+    /// had no other blocker and isn't part of the level geometry.
+    pub fn remove_blocker(&mut self, tile: IVec2) -> bool {
+        let Some(count) = self.blockers.get_mut(&tile) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+        self.blockers.remove(&tile);
+        !self.solids.contains(&tile)
     }
 
     /// Returns the IntGrid value at the given tile coordinate, or None if no tile exists.
     pub fn get_tile_value(&self, tile: IVec2) -> Option<i32> {
         self.tile_values.get(&tile).copied()
     }
+
+    /// Tile coordinate containing a world-space point.
+    pub fn world_to_tile(&self, point: Vec2) -> IVec2 {
+        ((point - self.origin) / self.tile_size).floor().as_ivec2()
+    }
+
+    /// World-space rectangle covered by a tile.
+    pub fn tile_to_world_rect(&self, tile: IVec2) -> Rect {
+        let min = self.origin + tile.as_vec2() * self.tile_size;
+        Rect::from_corners(min, min + self.tile_size)
+    }
+
+    /// Returns whether the tile containing a world-space point is solid.
+    pub fn is_solid_at(&self, point: Vec2) -> bool {
+        self.is_solid(self.world_to_tile(point))
+    }
+
+    /// Casts a ray from `origin` along `direction` (need not be normalised) for up to
+    /// `max_distance` world units, stepping tile by tile (DDA), and returns the first solid tile
+    /// hit. A ray starting inside a solid tile hits it immediately with a zero normal. A
+    /// non-finite or negative `max_distance` never hits anything.
+    pub fn raycast(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> Option<RayHit> {
+        // An unbounded ray would step forever once it is past the last solid tile.
+        if self.is_empty() || !max_distance.is_finite() || max_distance < 0.0 {
+            return None;
+        }
+        let direction = direction.try_normalize()?;
+        let mut tile = self.world_to_tile(origin);
+        if self.is_solid(tile) {
+            return Some(RayHit {
+                tile,
+                point: origin,
+                normal: Vec2::ZERO,
+            });
+        }
+
+        // `signum` maps 0.0 to 1 and -0.0 to -1; an axis the ray doesn't move along must not step,
+        // or a ray starting on a tile boundary divides 0 by 0 and never terminates.
+        let axis_step = |component: f32| {
+            if component > 0.0 {
+                1
+            } else if component < 0.0 {
+                -1
+            } else {
+                0
+            }
+        };
+        let step = IVec2::new(axis_step(direction.x), axis_step(direction.y));
+        // Distance along the ray between successive vertical / horizontal tile boundaries.
+        let delta = self.tile_size / direction.abs();
+        let rect = self.tile_to_world_rect(tile);
+        // Distance along the ray to the first vertical / horizontal tile boundary.
+        let mut next = Vec2::new(
+            match step.x {
+                1 => (rect.max.x - origin.x) / direction.x,
+                -1 => (rect.min.x - origin.x) / direction.x,
+                _ => f32::INFINITY,
+            },
+            match step.y {
+                1 => (rect.max.y - origin.y) / direction.y,
+                -1 => (rect.min.y - origin.y) / direction.y,
+                _ => f32::INFINITY,
+            },
+        );
+
+        loop {
+            let (distance, normal) = if next.x < next.y {
+                let crossing = (next.x, Vec2::new(-step.x as f32, 0.0));
+                tile.x += step.x;
+                next.x += delta.x;
+                crossing
+            } else {
+                let crossing = (next.y, Vec2::new(0.0, -step.y as f32));
+                tile.y += step.y;
+                next.y += delta.y;
+                crossing
+            };
+
+            if distance > max_distance {
+                return None;
+            }
+            if self.is_solid(tile) {
+                return Some(RayHit {
+                    tile,
+                    point: origin + direction * distance,
+                    normal,
+                });
+            }
+        }
+    }
+
+    /// Returns whether the segment between two world-space points is clear of solid tiles.
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        self.raycast(from, to - from, from.distance(to)).is_none()
+    }
+
+    /// Solid tiles overlapped by an axis-aligned box. Boxes that merely touch a tile edge do not
+    /// count as overlapping.
+    pub fn overlap_aabb(
+        &self,
+        center: Vec2,
+        half_extents: Vec2,
+    ) -> impl Iterator<Item = IVec2> + '_ {
+        let (min, max) = if self.is_empty() {
+            // Empty range, so an unbuilt map (zero tile size) never divides by zero.
+            (IVec2::ONE, IVec2::ZERO)
+        } else {
+            (
+                self.world_to_tile(center - half_extents + EDGE),
+                self.world_to_tile(center + half_extents - EDGE),
+            )
+        };
+
+        (min.y..=max.y)
+            .flat_map(move |ty| (min.x..=max.x).map(move |tx| IVec2::new(tx, ty)))
+            .filter(|tile| self.is_solid(*tile))
+    }
+
+    /// Returns whether an axis-aligned box overlaps any solid tile.
+    pub fn overlaps_solid(&self, center: Vec2, half_extents: Vec2) -> bool {
+        self.overlap_aabb(center, half_extents).next().is_some()
+    }
+
+    /// Moves an axis-aligned box by `motion` and returns the first solid tile it would run into.
+    /// Tiles the box only touches (e.g. the floor while walking) or already overlaps are ignored,
+    /// so a box that starts embedded can still move out.
+    pub fn sweep_aabb(&self, center: Vec2, half_extents: Vec2, motion: Vec2) -> Option<SweepHit> {
+        if self.is_empty() || motion == Vec2::ZERO {
+            return None;
+        }
+
+        let target = center + motion;
+        let min = self.world_to_tile(center.min(target) - half_extents);
+        let max = self.world_to_tile(center.max(target) + half_extents);
+        let inv = Vec2::ONE / motion;
+
+        let mut best: Option<SweepHit> = None;
+        for ty in min.y..=max.y {
+            for tx in min.x..=max.x {
+                let tile = IVec2::new(tx, ty);
+                if !self.is_solid(tile) {
+                    continue;
+                }
+
+                // Ray from the box centre against the tile grown by the box's half extents.
+                let rect = self.tile_to_world_rect(tile);
+                let grown_min = rect.min - half_extents;
+                let grown_max = rect.max + half_extents;
+
+                let mut entry = f32::NEG_INFINITY;
+                let mut exit = f32::INFINITY;
+                let mut normal = Vec2::ZERO;
+                let mut separated = false;
+                for axis in 0..2 {
+                    if motion[axis] == 0.0 {
+                        if center[axis] <= grown_min[axis] + EDGE
+                            || center[axis] >= grown_max[axis] - EDGE
+                        {
+                            separated = true;
+                        }
+                        continue;
+                    }
+
+                    let t0 = (grown_min[axis] - center[axis]) * inv[axis];
+                    let t1 = (grown_max[axis] - center[axis]) * inv[axis];
+                    let (near, far) = (t0.min(t1), t0.max(t1));
+                    if near > entry {
+                        entry = near;
+                        normal = Vec2::ZERO;
+                        normal[axis] = -motion[axis].signum();
+                    }
+                    exit = exit.min(far);
+                }
+
+                if separated || !(0.0..=1.0).contains(&entry) || entry >= exit {
+                    continue;
+                }
+                if !best.is_some_and(|hit| hit.time <= entry) {
+                    best = Some(SweepHit {
+                        tile,
+                        time: entry,
+                        position: center + motion * entry,
+                        normal,
+                    });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Finds the closest position to `center`, searching outward one tile step at a time up to
+    /// `max_tiles` away, where a box of `half_extents` does not overlap any solid tile.
+    pub fn nearest_free_position(
+        &self,
+        center: Vec2,
+        half_extents: Vec2,
+        max_tiles: i32,
+    ) -> Option<Vec2> {
+        if !self.overlaps_solid(center, half_extents) {
+            return Some(center);
+        }
+
+        for radius in 1..=max_tiles {
+            let ring = (-radius..=radius)
+                .flat_map(|dy| (-radius..=radius).map(move |dx| IVec2::new(dx, dy)))
+                .filter(|offset| offset.x.abs() == radius || offset.y.abs() == radius)
+                .map(|offset| center + offset.as_vec2() * self.tile_size)
+                .filter(|candidate| !self.overlaps_solid(*candidate, half_extents));
+
+            let closest = ring.min_by(|a, b| {
+                a.distance_squared(center)
+                    .total_cmp(&b.distance_squared(center))
+            });
+
+            if closest.is_some() {
+                return closest;
+            }
+        }
+
+        None
+    }
+}
+
+/// Whether the boxes centred at `a` and `b` overlap. Boxes that only touch along an edge don't.
+pub fn boxes_overlap(a: Vec2, a_half: Vec2, b: Vec2, b_half: Vec2) -> bool {
+    let gap = (a - b).abs() - (a_half + b_half);
+    gap.x < 0.0 && gap.y < 0.0
+}
+
+/// Collision tiles covered by an LDtk entity's bounds, in the same level-relative grid as
+/// `CollisionMap`. Assumes the entity layer shares the IntGrid layer's grid size.
+pub fn entity_tiles(
+    entity_instance: &EntityInstance,
+    layer_instance: &LayerInstance,
+) -> Vec<IVec2> {
+    // LDtk measures from the level's top-left corner with y down; the collision grid counts rows
+    // from the bottom.
+    let grid = layer_instance.grid_size as f32;
+    let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+    let top_left = entity_instance.px.as_vec2() - entity_instance.pivot * size;
+    let min = (top_left / grid).floor().as_ivec2();
+    let max = ((top_left + size) / grid).ceil().as_ivec2() - IVec2::ONE;
+
+    (min.y..=max.y)
+        .flat_map(|row| {
+            (min.x..=max.x).map(move |column| IVec2::new(column, layer_instance.c_hei - 1 - row))
+        })
+        .collect()
 }
 
 /// Regenerates the solid tile cache whenever LDtk emits level spawn/despawn events. The ECS query
 /// iterates over freshly spawned `IntGridCell` entities, copying only the coordinates we care about
 /// into the `HashSet`. All intermediate data is stack-allocated and dropped after the system runs.
-fn rebuild_collision_map(
+pub fn rebuild_collision_map(
     mut events: EventReader<LevelEvent>,
     int_cells: Query<(&GridCoords, &IntGridCell, &Parent)>,
     config: Res<LevelConfig>,
     level_assets: Res<LevelAssets>,
     mut map: ResMut<CollisionMap>,
+    mut rebuilt: EventWriter<CollisionMapRebuilt>,
 ) {
     let mut needs_rebuild = false;
     let mut should_clear = false;
@@ -89,8 +450,7 @@ fn rebuild_collision_map(
 
     map.tile_size = Vec2::splat(config.tile_size);
     map.origin = level_assets.level_origin.unwrap_or(Vec2::ZERO);
-    map.solids.clear();
-    map.tile_values.clear();
+    map.clear();
 
     let mut value_2_count = 0;
     for (coords, cell, _) in &int_cells {
@@ -99,7 +459,8 @@ fn rebuild_collision_map(
         if cell.value > 0 {
             // Value 1 = solid collision block
             // Value 2 = non-solid trigger (for level transitions)
-            if cell.value == 1 {
+            // Values 3 and 4 = solid, but removable at runtime (see `breakable`)
+            if matches!(cell.value, 1 | BREAKABLE_TILE | CRUMBLING_TILE) {
                 map.solids.insert(tile_pos);
             }
 
@@ -114,6 +475,7 @@ fn rebuild_collision_map(
     }
 
     info!("Collision map rebuilt: {} solid tiles, {} trigger tiles", map.solids.len(), value_2_count);
+    rebuilt.send(CollisionMapRebuilt);
 
     if map.solids.is_empty() {
         warn!(
@@ -121,3 +483,260 @@ fn rebuild_collision_map(
         );
     }
 }
+
+pub fn collision_debug_enabled(debug: Res<CollisionDebug>) -> bool {
+    debug.enabled
+}
+
+/// Outlines every non-empty IntGrid tile (solids in red, triggers in yellow), runtime blockers in
+/// cyan, and every collider in green. Gizmos are immediate-mode, so nothing needs cleaning up when
+/// the flag is cleared.
+fn draw_collision_debug(
+    map: Res<CollisionMap>,
+    colliders: Query<(&Transform, &Collider)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile, value) in &map.tile_values {
+        let center = map.origin + (tile.as_vec2() + 0.5) * map.tile_size;
+        let color = if map.is_solid(*tile) {
+            Color::srgb(1.0, 0.2, 0.2)
+        } else if *value == 2 {
+            Color::srgb(1.0, 0.9, 0.2)
+        } else {
+            Color::srgb(0.6, 0.6, 0.6)
+        };
+        gizmos.rect_2d(center, 0.0, map.tile_size, color);
+    }
+
+    for tile in map.blockers.keys() {
+        let center = map.tile_to_world_rect(*tile).center();
+        gizmos.rect_2d(center, 0.0, map.tile_size, Color::srgb(0.2, 0.85, 1.0));
+    }
+
+    for (transform, collider) in &colliders {
+        gizmos.rect_2d(
+            collider.center(transform.translation.truncate()),
+            0.0,
+            collider.half_extents * 2.0,
+            Color::srgb(0.2, 1.0, 0.4),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16-px tiles with the grid origin at the world origin.
+    fn map_with(tiles: &[(i32, i32)]) -> CollisionMap {
+        let mut map = CollisionMap {
+            tile_size: Vec2::splat(16.0),
+            ..default()
+        };
+        for &(x, y) in tiles {
+            let tile = IVec2::new(x, y);
+            map.solids.insert(tile);
+            map.tile_values.insert(tile, 1);
+        }
+        map
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-3),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn boxes_overlap_only_when_they_intersect() {
+        let half = Vec2::splat(8.0);
+        assert!(boxes_overlap(Vec2::ZERO, half, Vec2::new(15.0, 15.0), half));
+        assert!(!boxes_overlap(Vec2::ZERO, half, Vec2::new(16.0, 0.0), half));
+        assert!(!boxes_overlap(Vec2::ZERO, half, Vec2::new(0.0, 20.0), half));
+    }
+
+    #[test]
+    fn raycast_hits_each_face() {
+        // Tile (2, 0) spans x 32..48, y 0..16.
+        let map = map_with(&[(2, 0)]);
+        let cases = [
+            (
+                Vec2::new(8.0, 8.0),
+                Vec2::X,
+                Vec2::new(32.0, 8.0),
+                Vec2::NEG_X,
+            ),
+            (
+                Vec2::new(72.0, 8.0),
+                Vec2::NEG_X,
+                Vec2::new(48.0, 8.0),
+                Vec2::X,
+            ),
+            (
+                Vec2::new(40.0, -24.0),
+                Vec2::Y,
+                Vec2::new(40.0, 0.0),
+                Vec2::NEG_Y,
+            ),
+            (
+                Vec2::new(40.0, 40.0),
+                Vec2::NEG_Y,
+                Vec2::new(40.0, 16.0),
+                Vec2::Y,
+            ),
+        ];
+
+        for (origin, direction, point, normal) in cases {
+            let hit = map.raycast(origin, direction, 100.0).unwrap();
+            assert_eq!(hit.tile, IVec2::new(2, 0));
+            assert_near(hit.point, point);
+            assert_eq!(hit.normal, normal);
+        }
+    }
+
+    #[test]
+    fn raycast_diagonal_reports_the_face_it_crosses() {
+        let map = map_with(&[(2, 1)]);
+        // Climbs through (1, 0) and (1, 1) before entering (2, 1) through its left face.
+        let hit = map
+            .raycast(Vec2::new(8.0, 8.0), Vec2::new(2.0, 1.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.tile, IVec2::new(2, 1));
+        assert_near(hit.point, Vec2::new(32.0, 20.0));
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn axis_aligned_rays_ignore_the_sign_of_zero() {
+        let map = map_with(&[(3, 0), (0, -3)]);
+
+        // Origins sit exactly on tile boundaries, where stepping the idle axis used to produce
+        // 0 / 0 and loop forever.
+        for direction in [Vec2::new(1.0, 0.0), Vec2::new(1.0, -0.0)] {
+            let hit = map.raycast(Vec2::new(8.0, 0.0), direction, 100.0).unwrap();
+            assert_eq!(hit.tile, IVec2::new(3, 0));
+            assert_near(hit.point, Vec2::new(48.0, 0.0));
+            assert_eq!(hit.normal, Vec2::NEG_X);
+        }
+        for direction in [Vec2::new(0.0, -1.0), Vec2::new(-0.0, -1.0)] {
+            let hit = map.raycast(Vec2::new(0.0, 8.0), direction, 100.0).unwrap();
+            assert_eq!(hit.tile, IVec2::new(0, -3));
+            assert_near(hit.point, Vec2::new(0.0, -32.0));
+            assert_eq!(hit.normal, Vec2::Y);
+        }
+    }
+
+    #[test]
+    fn raycast_starting_inside_a_solid_tile_hits_immediately() {
+        let map = map_with(&[(2, 0)]);
+        let origin = Vec2::new(40.0, 8.0);
+        let hit = map.raycast(origin, Vec2::X, 100.0).unwrap();
+        assert_eq!(hit.tile, IVec2::new(2, 0));
+        assert_eq!(hit.point, origin);
+        assert_eq!(hit.normal, Vec2::ZERO);
+    }
+
+    #[test]
+    fn raycast_respects_max_distance() {
+        let map = map_with(&[(2, 0)]);
+        let origin = Vec2::new(8.0, 8.0);
+        assert!(map.raycast(origin, Vec2::X, 24.5).is_some());
+        assert!(map.raycast(origin, Vec2::X, 20.0).is_none());
+        assert!(map.raycast(origin, Vec2::NEG_X, f32::INFINITY).is_none());
+        assert!(map.raycast(origin, Vec2::X, f32::NAN).is_none());
+        assert!(map.raycast(origin, Vec2::ZERO, 100.0).is_none());
+        assert!(map.line_of_sight(origin, Vec2::new(30.0, 8.0)));
+        assert!(!map.line_of_sight(origin, Vec2::new(60.0, 8.0)));
+    }
+
+    #[test]
+    fn boxes_touching_a_tile_edge_do_not_overlap_it() {
+        let map = map_with(&[(2, 0)]);
+        let half = Vec2::splat(8.0);
+
+        // Flush against the left face, resting on top, and touching a corner.
+        for center in [
+            Vec2::new(24.0, 8.0),
+            Vec2::new(40.0, 24.0),
+            Vec2::new(24.0, 24.0),
+        ] {
+            assert!(!map.overlaps_solid(center, half), "{center} overlaps");
+        }
+
+        let overlapping: Vec<_> = map.overlap_aabb(Vec2::new(25.0, 8.0), half).collect();
+        assert_eq!(overlapping, vec![IVec2::new(2, 0)]);
+    }
+
+    #[test]
+    fn sweep_reports_entry_time_and_normal() {
+        let map = map_with(&[(2, 0), (0, -1), (1, -1), (2, -1), (3, -1)]);
+        let half = Vec2::splat(4.0);
+
+        // Sliding right into the left face of (2, 0); the box's right edge reaches x = 32.
+        let hit = map
+            .sweep_aabb(Vec2::new(20.0, 8.0), half, Vec2::new(20.0, 0.0))
+            .unwrap();
+        assert_eq!(hit.tile, IVec2::new(2, 0));
+        assert!((hit.time - 0.4).abs() < 1e-4);
+        assert_near(hit.position, Vec2::new(28.0, 8.0));
+        assert_eq!(hit.normal, Vec2::NEG_X);
+
+        // Falling onto the top of (2, 0).
+        let hit = map
+            .sweep_aabb(Vec2::new(40.0, 40.0), half, Vec2::new(0.0, -40.0))
+            .unwrap();
+        assert_eq!(hit.tile, IVec2::new(2, 0));
+        assert!((hit.time - 0.5).abs() < 1e-4);
+        assert_near(hit.position, Vec2::new(40.0, 20.0));
+        assert_eq!(hit.normal, Vec2::Y);
+    }
+
+    #[test]
+    fn sweep_ignores_touching_and_embedded_tiles() {
+        let map = map_with(&[(0, -1), (1, -1), (2, -1), (3, -1), (2, 1)]);
+        let half = Vec2::splat(4.0);
+
+        // Walking along the floor row.
+        assert!(map
+            .sweep_aabb(Vec2::new(8.0, 4.0), half, Vec2::new(40.0, 0.0))
+            .is_none());
+        // Starting inside (2, 1) and moving out of it.
+        assert!(map
+            .sweep_aabb(Vec2::new(40.0, 24.0), half, Vec2::new(0.0, 20.0))
+            .is_none());
+        assert!(map
+            .sweep_aabb(Vec2::new(8.0, 4.0), half, Vec2::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn removing_a_blocker_keeps_the_level_geometry_under_it() {
+        let mut map = map_with(&[(0, 0)]);
+        let wall = IVec2::new(0, 0);
+        let gap = IVec2::new(1, 0);
+
+        assert!(!map.add_blocker(wall));
+        assert!(map.add_blocker(gap));
+        assert!(!map.remove_blocker(wall));
+        assert!(map.remove_blocker(gap));
+
+        assert!(map.is_solid(wall));
+        assert!(!map.is_solid(gap));
+        assert!(map.blockers.is_empty());
+    }
+
+    #[test]
+    fn overlapping_blockers_are_counted() {
+        let mut map = map_with(&[]);
+        let tile = IVec2::new(2, 3);
+
+        assert!(map.add_blocker(tile));
+        assert!(!map.add_blocker(tile));
+        assert!(!map.remove_blocker(tile));
+        assert!(map.is_solid(tile));
+        assert!(map.remove_blocker(tile));
+        assert!(!map.is_solid(tile));
+        assert!(!map.remove_blocker(tile));
+    }
+}
@@ -0,0 +1,264 @@
+//! Melee combat. Pressing attack plays the player's `Attack` clip and spawns a `Hitbox` in front
+//! of them; the hitbox is only live during the clip's `hit_frames`, so what the player sees and
+//! what connects stay in step. A hit damages the target's `Hurtbox` owner, knocks it back with the
+//! hitbox's knockback, and freezes the game for a few frames (hit-stop) to sell the impact. Swings
+//! also break breakable tiles.
+//!
+//! Hit-stop slows `Time<Virtual>` rather than pausing the state machine, so every gameplay system
+//! freezes together while the window, input, and UI keep running.
+
+use bevy::prelude::*;
+
+use crate::animation::{AnimationLibrary, AnimationPlayer, AnimationState, PlayerAnimations};
+use crate::breakable::StrikeTile;
+use crate::cinematic::CameraDirector;
+use crate::collision::{collision_debug_enabled, CollisionMap, BREAKABLE_TILE};
+use crate::health::{DamageEvent, Faction, Hurtbox};
+use crate::movement::{Collider, MovementState};
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the attack, hitbox, and hit-stop systems.
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStop>()
+            .add_systems(
+                Update,
+                (
+                    start_player_attack.in_set(GameSet::Input),
+                    (update_melee_attacks, resolve_hitboxes)
+                        .chain()
+                        .in_set(GameSet::Effects),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, update_hit_stop)
+            .add_systems(Update, draw_hitbox_debug.run_if(collision_debug_enabled));
+    }
+}
+
+/// Attack timing used when the clip file has no `Attack` clip (or no `hit_frames`).
+const DEFAULT_ATTACK_DURATION: f32 = 0.3;
+const DEFAULT_HIT_WINDOW: (f32, f32) = (0.06, 0.2);
+
+/// Player sword swing, relative to the collider centre when facing right.
+const PLAYER_HITBOX_OFFSET: Vec2 = Vec2::new(16.0, 0.0);
+const PLAYER_HITBOX_SIZE: Vec2 = Vec2::new(22.0, 20.0);
+const PLAYER_KNOCKBACK: Vec2 = Vec2::new(260.0, 160.0);
+
+/// Real-time seconds the game freezes for when a hit lands.
+const HIT_STOP_SECONDS: f32 = 0.06;
+
+/// Damaging area owned by an attacker. Its position follows the owner's collider centre plus
+/// `offset`, which is already mirrored for the direction the attack faces.
+#[derive(Component, Debug)]
+pub struct Hitbox {
+    pub owner: Entity,
+    pub offset: Vec2,
+    pub half_extents: Vec2,
+    pub damage: i32,
+    /// Launch velocity given to targets; `x` is mirrored to push them away from the owner.
+    pub knockback: Vec2,
+    pub faction: Faction,
+    pub active: bool,
+    /// Targets already hit, so one swing hits each target once.
+    struck: Vec<Entity>,
+}
+
+/// An attack in progress on its owner. Times are in seconds since the attack started.
+#[derive(Component, Debug)]
+pub struct MeleeAttack {
+    elapsed: f32,
+    duration: f32,
+    hit_window: (f32, f32),
+    hitbox: Entity,
+}
+
+/// Remaining real time of the current hit-stop.
+#[derive(Resource, Default)]
+pub struct HitStop {
+    remaining: f32,
+}
+
+impl HitStop {
+    /// Freezes gameplay for `seconds` of real time, extending any freeze already running.
+    pub fn trigger(&mut self, seconds: f32) {
+        self.remaining = self.remaining.max(seconds);
+    }
+}
+
+/// Starts a swing when `J` is pressed and the player isn't already attacking or in hit-stun. Timing
+/// comes from the `Attack` clip so the hitbox lines up with the drawn frames.
+#[allow(clippy::type_complexity)]
+fn start_player_attack(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    director: Res<CameraDirector>,
+    animations: Res<PlayerAnimations>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut player: Query<
+        (Entity, &Sprite, &MovementState, Option<&mut AnimationPlayer>),
+        (With<Player>, Without<MeleeAttack>),
+    >,
+) {
+    if director.is_playing() || !keyboard.just_pressed(KeyCode::KeyJ) {
+        return;
+    }
+    let Ok((entity, sprite, state, animation)) = player.get_single_mut() else {
+        return;
+    };
+    if state.is_stunned() {
+        return;
+    }
+
+    let clip = libraries
+        .get(&animations.0)
+        .and_then(|library| library.clips.get(&AnimationState::Attack));
+    let duration = clip.map_or(DEFAULT_ATTACK_DURATION, |clip| clip.duration());
+    let hit_window = clip
+        .and_then(|clip| clip.hit_window())
+        .unwrap_or(DEFAULT_HIT_WINDOW);
+
+    let facing = if sprite.flip_x { -1.0 } else { 1.0 };
+    let hitbox = commands
+        .spawn((
+            Name::new("PlayerHitbox"),
+            Hitbox {
+                owner: entity,
+                offset: PLAYER_HITBOX_OFFSET * Vec2::new(facing, 1.0),
+                half_extents: PLAYER_HITBOX_SIZE * 0.5,
+                damage: 1,
+                knockback: PLAYER_KNOCKBACK,
+                faction: Faction::Player,
+                active: false,
+                struck: Vec::new(),
+            },
+        ))
+        .id();
+
+    // Parented so the hitbox goes away with its owner, even mid-swing.
+    commands.entity(entity).add_child(hitbox).insert(MeleeAttack {
+        elapsed: 0.0,
+        duration: duration.max(hit_window.1),
+        hit_window,
+        hitbox,
+    });
+
+    if let Some(mut animation) = animation {
+        animation.play_attack();
+    }
+}
+
+/// Advances attacks, switching their hitbox on for the hit window and cleaning up when the swing
+/// ends.
+fn update_melee_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut attackers: Query<(Entity, &mut MeleeAttack, Option<&mut AnimationPlayer>)>,
+    mut hitboxes: Query<&mut Hitbox>,
+) {
+    for (entity, mut attack, animation) in &mut attackers {
+        attack.elapsed += time.delta_seconds();
+        let (start, end) = attack.hit_window;
+
+        if let Ok(mut hitbox) = hitboxes.get_mut(attack.hitbox) {
+            hitbox.active = attack.elapsed >= start && attack.elapsed < end;
+        }
+
+        if attack.elapsed >= attack.duration {
+            commands.entity(attack.hitbox).despawn_recursive();
+            commands.entity(entity).remove::<MeleeAttack>();
+            if let Some(mut animation) = animation {
+                animation.stop_attack();
+            }
+        }
+    }
+}
+
+/// Damages and knocks back hostile hurtboxes overlapped by live hitboxes. Any hit triggers
+/// hit-stop. Breakable tiles the hitbox reaches are struck as well.
+fn resolve_hitboxes(
+    mut hitboxes: Query<&mut Hitbox>,
+    owners: Query<(&Transform, Option<&Collider>), Without<Hitbox>>,
+    targets: Query<(Entity, &Transform, &Hurtbox, &Faction), Without<Hitbox>>,
+    map: Res<CollisionMap>,
+    mut damage: EventWriter<DamageEvent>,
+    mut strikes: EventWriter<StrikeTile>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    for mut hitbox in &mut hitboxes {
+        if !hitbox.active {
+            continue;
+        }
+        let Ok((owner_transform, owner_collider)) = owners.get(hitbox.owner) else {
+            continue;
+        };
+        let owner = owner_transform.translation.truncate();
+        let origin = owner_collider.map_or(owner, |collider| collider.center(owner));
+        let center = origin + hitbox.offset;
+
+        for tile in map.overlap_aabb(center, hitbox.half_extents) {
+            if map.get_tile_value(tile) == Some(BREAKABLE_TILE) {
+                strikes.send(StrikeTile { tile });
+            }
+        }
+
+        for (target, transform, hurtbox, faction) in &targets {
+            let position = transform.translation.truncate();
+            if target == hitbox.owner
+                || !hitbox.faction.is_hostile_to(*faction)
+                || hitbox.struck.contains(&target)
+                || !hurtbox.overlaps(position, center, hitbox.half_extents)
+            {
+                continue;
+            }
+
+            hitbox.struck.push(target);
+            // Knock back away from the attacker rather than the hitbox, which may have reached
+            // past the target's centre.
+            damage.send(DamageEvent {
+                target,
+                amount: hitbox.damage,
+                source: Some(origin),
+                knockback: Some(hitbox.knockback),
+            });
+            hit_stop.trigger(HIT_STOP_SECONDS);
+        }
+    }
+}
+
+/// Holds virtual time still while hit-stop is running. Counts down in real time, since virtual
+/// time isn't advancing.
+fn update_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    if hit_stop.remaining > 0.0 {
+        hit_stop.remaining = (hit_stop.remaining - real_time.delta_seconds()).max(0.0);
+        let speed = if hit_stop.remaining > 0.0 { 0.0 } else { 1.0 };
+        virtual_time.set_relative_speed(speed);
+    }
+}
+
+fn draw_hitbox_debug(
+    hitboxes: Query<&Hitbox>,
+    owners: Query<(&Transform, Option<&Collider>)>,
+    mut gizmos: Gizmos,
+) {
+    for hitbox in &hitboxes {
+        let Ok((transform, collider)) = owners.get(hitbox.owner) else {
+            continue;
+        };
+        let owner = transform.translation.truncate();
+        let origin = collider.map_or(owner, |collider| collider.center(owner));
+        let color = if hitbox.active {
+            Color::srgb(1.0, 0.4, 0.1)
+        } else {
+            Color::srgb(0.5, 0.3, 0.2)
+        };
+        gizmos.rect_2d(origin + hitbox.offset, 0.0, hitbox.half_extents * 2.0, color);
+    }
+}
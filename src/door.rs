@@ -0,0 +1,171 @@
+//! Locked doors. A `LockedDoor` blocks movement like a wall until the player stands next to it and
+//! interacts with it (`E`, see `interact`) while carrying a key of its colour, which uses up the
+//! key. Keys themselves are collectibles (see `collectible`), so the `CollectibleTally` doubles as
+//! the player's key ring.
+//!
+//! Doors aren't part of the IntGrid, so their tiles are added to the `CollisionMap` as blockers
+//! after every rebuild and removed again when the door opens, leaving any wall tiles the door
+//! overlaps in place. Opened doors are remembered per level IID in `OpenDoors`, so they stay open
+//! when the level is reloaded or re-entered.
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::collectible::{CollectibleKind, CollectibleTally, KeyColor};
+use crate::collision::{
+    entity_tiles, rebuild_collision_map, CollisionMap, CollisionMapChanged, CollisionMapRebuilt,
+    CollisionSystems,
+};
+use crate::interact::{pick_interaction, Interact, Interactable};
+use crate::level::{LevelAssets, LevelIids};
+use crate::state::{GameSet, GameState};
+
+/// Registers the door LDtk entity, the opened-door memory, and the unlock system.
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenDoors>()
+            .register_ldtk_entity::<LockedDoorBundle>("LockedDoor")
+            .add_systems(
+                PostUpdate,
+                apply_door_solids
+                    .after(rebuild_collision_map)
+                    .in_set(CollisionSystems),
+            )
+            .add_systems(
+                Update,
+                unlock_doors
+                    .after(pick_interaction)
+                    .in_set(GameSet::Input)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// How close (in pixels) the player's collider must be to a door to unlock it.
+const UNLOCK_REACH: f32 = 4.0;
+
+/// IIDs of opened doors, keyed by the IID of the level they are in.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct OpenDoors(pub LevelIids);
+
+/// LDtk-authored door, solid until unlocked with a key of its `Color` (same enum as `Key`). It
+/// blocks every tile its bounds touch, so the entity layer's grid must match the IntGrid layer's.
+#[derive(Component, Clone, Debug)]
+pub struct LockedDoor {
+    pub color: KeyColor,
+    /// Collision tiles covered by the door, in the same level-relative grid as `CollisionMap`.
+    pub tiles: Vec<IVec2>,
+    iid: String,
+}
+
+#[derive(Bundle)]
+struct LockedDoorBundle {
+    door: LockedDoor,
+    interactable: Interactable,
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for LockedDoorBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let color = KeyColor::from_field(entity_instance);
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+
+        Self {
+            door: LockedDoor {
+                color,
+                tiles: entity_tiles(entity_instance, layer_instance),
+                iid: entity_instance.iid.clone(),
+            },
+            interactable: Interactable {
+                half_extents: size * 0.5,
+                reach: UNLOCK_REACH,
+            },
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: color.color().with_luminance(0.3),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// Re-applies closed doors to the freshly rebuilt collision map, and despawns doors that were
+/// opened on an earlier visit.
+fn apply_door_solids(
+    mut commands: Commands,
+    mut rebuilt: EventReader<CollisionMapRebuilt>,
+    level_assets: Res<LevelAssets>,
+    open_doors: Res<OpenDoors>,
+    doors: Query<(Entity, &LockedDoor)>,
+    mut map: ResMut<CollisionMap>,
+    mut changed: EventWriter<CollisionMapChanged>,
+) {
+    if rebuilt.read().count() == 0 {
+        return;
+    }
+    let level_iid = level_assets.level_iid.as_deref().unwrap_or_default();
+
+    let mut added = Vec::new();
+    for (entity, door) in &doors {
+        if open_doors.contains(level_iid, &door.iid) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        added.extend(door.tiles.iter().filter(|tile| map.add_blocker(**tile)));
+    }
+
+    if !added.is_empty() {
+        changed.send(CollisionMapChanged { tiles: added });
+    }
+}
+
+/// Opens the door the player interacts with if a matching key is in the tally, removing the door's
+/// blockers from the collision map.
+#[allow(clippy::too_many_arguments)]
+fn unlock_doors(
+    mut commands: Commands,
+    mut interactions: EventReader<Interact>,
+    level_assets: Res<LevelAssets>,
+    mut tally: ResMut<CollectibleTally>,
+    mut open_doors: ResMut<OpenDoors>,
+    mut map: ResMut<CollisionMap>,
+    mut changed: EventWriter<CollisionMapChanged>,
+    doors: Query<&LockedDoor>,
+) {
+    for interaction in interactions.read() {
+        let Ok(door) = doors.get(interaction.target) else {
+            continue;
+        };
+        if !tally.take(CollectibleKind::Key(door.color)) {
+            info!("Door is locked; it needs a {:?} key", door.color);
+            continue;
+        }
+
+        let level_iid = level_assets.level_iid.as_deref().unwrap_or_default();
+        open_doors.insert(level_iid, &door.iid);
+
+        let removed: Vec<IVec2> = door
+            .tiles
+            .iter()
+            .copied()
+            .filter(|tile| map.remove_blocker(*tile))
+            .collect();
+        if !removed.is_empty() {
+            changed.send(CollisionMapChanged { tiles: removed });
+        }
+        commands.entity(interaction.target).despawn_recursive();
+        info!("Opened {:?} door", door.color);
+    }
+}
@@ -0,0 +1,585 @@
+//! Walking enemies placed as `Enemy` entities in LDtk. Each one patrols between its authored
+//! points (or back and forth between walls and ledges when it has none), chases the player while
+//! it can see them, keeps heading for where it last saw them for a moment after losing sight, and
+//! then walks back home. Touching an enemy hurts the player.
+//!
+//! The LDtk entity only marks where an enemy starts. The enemy itself is a separate top-level
+//! entity driven by the shared kinematic movement systems, so its translation is in world space
+//! like the player's. Spawned enemies are remembered by LDtk IID until the next level load, which
+//! keeps killed enemies dead and stops hot reloads from duplicating the living ones.
+
+use std::collections::{HashSet, VecDeque};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_ldtk::utils::{
+    ldtk_grid_coords_to_translation, ldtk_pixel_coords_to_translation_pivoted,
+};
+
+use crate::collision::{boxes_overlap, collision_debug_enabled, CollisionMap};
+use crate::health::{DamageEvent, Faction, Health, Hurtbox};
+use crate::level::LevelAssets;
+use crate::movement::{Collider, MovementController, MovementSettings, MovementState, Velocity};
+use crate::navigation::{JumpReach, NavGraph, NavLinkKind, NavStep};
+use crate::player::Player;
+use crate::projectile::{ProjectileSpec, SpawnProjectile};
+use crate::state::{GameSet, GameState};
+
+/// Registers the LDtk spawn marker plus the spawning, AI, and contact damage systems.
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyRoster>()
+            .register_ldtk_entity::<EnemySpawnBundle>("Enemy")
+            .add_systems(OnEnter(GameState::Loading), despawn_enemies)
+            .add_systems(
+                Update,
+                (
+                    spawn_enemies.before(GameSet::Input),
+                    (enemy_ai, enemy_fire_arrows).chain().in_set(GameSet::Input),
+                    face_movement.after(GameSet::Movement),
+                    enemy_contact_damage.in_set(GameSet::Effects),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, draw_sight_debug.run_if(collision_debug_enabled));
+    }
+}
+
+/// How close (in world units) an enemy has to get to a waypoint or its home to count as arrived.
+const ARRIVE_DISTANCE: f32 = 2.0;
+
+/// Seconds a chasing enemy waits after losing sight of the player before heading home.
+const LOSE_SIGHT_SECONDS: f32 = 1.5;
+
+/// Seconds between path searches while chasing or returning home.
+const REPATH_SECONDS: f32 = 0.5;
+
+/// How far below an airborne target to look for the node it will land on.
+const GOAL_SEARCH_TILES: i32 = 8;
+
+/// What an enemy is currently trying to do.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyBehaviour {
+    #[default]
+    Patrol,
+    Chase,
+    Return,
+}
+
+/// AI state and tuning for one enemy. Speeds are in world units per second; the movement
+/// controller's ground speed is set to `chase_speed` and patrolling scales the input axis down.
+#[derive(Component, Clone, Debug)]
+pub struct Enemy {
+    pub behaviour: EnemyBehaviour,
+    /// Collider centre the enemy spawned at.
+    pub home: Vec2,
+    /// World-space x coordinates to walk between, in order.
+    patrol: Vec<f32>,
+    patrol_index: usize,
+    pub patrol_speed: f32,
+    pub chase_speed: f32,
+    pub sight_range: f32,
+    pub contact_damage: i32,
+    /// Seconds between arrows while chasing; 0 for enemies that only fight up close.
+    pub arrow_interval: f32,
+    arrow_timer: f32,
+    /// -1.0 for left, 1.0 for right.
+    facing: f32,
+    /// Seconds since a chasing enemy last saw the player.
+    unseen: f32,
+    /// Player's foot point when last seen. Chasing enemies keep heading there after losing sight,
+    /// e.g. when the player hops onto a platform above.
+    last_seen: Option<Vec2>,
+    /// Remaining navigation steps towards the player or home.
+    path: VecDeque<NavStep>,
+    /// Seconds until the path is searched again.
+    repath: f32,
+}
+
+/// LDtk-authored enemy start point.
+///
+/// Fields (all optional): `Patrol` (Array of Points), `Speed` (Float, default 60), `ChaseSpeed`
+/// (Float, default 110), `SightRange` (Float, default 160), `ArrowInterval` (Float, seconds
+/// between shots, default 0 for no arrows), `ContactDamage` (Int, default 1) and `Health` (Int,
+/// default 2).
+#[derive(Component, Clone, Debug)]
+pub struct EnemySpawn {
+    iid: String,
+    /// Centre relative to the level's bottom-left corner.
+    position: Vec2,
+    size: Vec2,
+    /// Patrol points relative to the level's bottom-left corner.
+    patrol: Vec<Vec2>,
+    speed: f32,
+    chase_speed: f32,
+    sight_range: f32,
+    arrow_interval: f32,
+    contact_damage: i32,
+    health: i32,
+}
+
+#[derive(Bundle)]
+struct EnemySpawnBundle {
+    spawn: EnemySpawn,
+}
+
+impl LdtkEntity for EnemySpawnBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = IVec2::new(entity_instance.width, entity_instance.height);
+        let grid_size = layer_instance.grid_size;
+
+        let position = ldtk_pixel_coords_to_translation_pivoted(
+            entity_instance.px,
+            layer_instance.c_hei * grid_size,
+            size,
+            entity_instance.pivot,
+        );
+        let patrol = entity_instance
+            .iter_points_field("Patrol")
+            .map(|points| {
+                points
+                    .map(|point| {
+                        ldtk_grid_coords_to_translation(
+                            *point,
+                            layer_instance.c_hei,
+                            IVec2::splat(grid_size),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let float_field = |identifier: &str, default: f32| {
+            entity_instance
+                .get_float_field(identifier)
+                .copied()
+                .unwrap_or(default)
+        };
+        let int_field = |identifier: &str, default: i32| {
+            entity_instance
+                .get_int_field(identifier)
+                .copied()
+                .unwrap_or(default)
+        };
+
+        Self {
+            spawn: EnemySpawn {
+                iid: entity_instance.iid.clone(),
+                position,
+                size: size.as_vec2(),
+                patrol,
+                speed: float_field("Speed", 60.0),
+                chase_speed: float_field("ChaseSpeed", 110.0),
+                sight_range: float_field("SightRange", 160.0),
+                arrow_interval: float_field("ArrowInterval", 0.0),
+                contact_damage: int_field("ContactDamage", 1),
+                health: int_field("Health", 2),
+            },
+        }
+    }
+}
+
+/// IIDs of the enemies spawned since the level was loaded.
+#[derive(Resource, Default)]
+struct EnemyRoster {
+    spawned: HashSet<String>,
+}
+
+fn despawn_enemies(
+    mut commands: Commands,
+    mut roster: ResMut<EnemyRoster>,
+    query: Query<Entity, With<Enemy>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    roster.spawned.clear();
+}
+
+/// Spawns an enemy for every spawn marker that hasn't produced one yet, once the level origin is
+/// known.
+fn spawn_enemies(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    asset_server: Res<AssetServer>,
+    mut roster: ResMut<EnemyRoster>,
+    spawns: Query<&EnemySpawn>,
+) {
+    let Some(origin) = level_assets.level_origin else {
+        return;
+    };
+
+    for spawn in &spawns {
+        if !roster.spawned.insert(spawn.iid.clone()) {
+            continue;
+        }
+
+        let home = origin + spawn.position;
+        let chase_speed = spawn.chase_speed.max(spawn.speed).max(1.0);
+
+        commands.spawn((
+            Name::new("Enemy"),
+            Enemy {
+                behaviour: EnemyBehaviour::Patrol,
+                home,
+                patrol: spawn.patrol.iter().map(|point| origin.x + point.x).collect(),
+                patrol_index: 0,
+                patrol_speed: spawn.speed,
+                chase_speed,
+                sight_range: spawn.sight_range,
+                contact_damage: spawn.contact_damage,
+                arrow_interval: spawn.arrow_interval,
+                arrow_timer: spawn.arrow_interval,
+                facing: 1.0,
+                unseen: 0.0,
+                last_seen: None,
+                path: VecDeque::new(),
+                repath: 0.0,
+            },
+            SpriteBundle {
+                texture: asset_server.load("textures/blob.png"),
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.45, 0.4),
+                    custom_size: Some(spawn.size),
+                    ..default()
+                },
+                // Just behind the player.
+                transform: Transform::from_translation(home.extend(150.0)),
+                ..default()
+            },
+            Velocity::default(),
+            MovementState::default(),
+            MovementController {
+                ground_max_speed: chase_speed,
+                air_max_speed: chase_speed,
+                ..default()
+            },
+            Collider::from_size(spawn.size),
+            Hurtbox::from_collider(&Collider::from_size(spawn.size)),
+            Faction::Enemy,
+            Health::new(spawn.health),
+        ));
+    }
+}
+
+/// Picks each enemy's behaviour and writes its movement intent. Chasing and returning enemies
+/// follow a path over the `NavGraph` when one exists, jumping and dropping between platforms.
+/// Otherwise ledges and walls are probed with `CollisionMap` queries one step ahead: patrolling
+/// enemies turn around, chasing and returning ones stop rather than walk off an edge.
+fn enemy_ai(
+    time: Res<Time>,
+    map: Res<CollisionMap>,
+    graph: Res<NavGraph>,
+    movement: Res<MovementSettings>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    mut enemies: Query<
+        (
+            &mut Enemy,
+            &mut MovementState,
+            &MovementController,
+            &Transform,
+            &Collider,
+        ),
+        Without<Player>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    let player = player_query.get_single().ok().map(|(transform, collider)| {
+        let center = collider.center(transform.translation.truncate());
+        (center, foot_point(center, collider.half_extents))
+    });
+
+    for (mut enemy, mut state, controller, transform, collider) in &mut enemies {
+        let center = collider.center(transform.translation.truncate());
+        let feet = foot_point(center, collider.half_extents);
+        let player_center = player.map(|(center, _)| center);
+        let sees_player = player_center.is_some_and(|player| {
+            center.distance(player) <= enemy.sight_range && map.line_of_sight(center, player)
+        });
+
+        if sees_player {
+            if enemy.behaviour != EnemyBehaviour::Chase {
+                enemy.repath = 0.0;
+            }
+            enemy.behaviour = EnemyBehaviour::Chase;
+            enemy.unseen = 0.0;
+            enemy.last_seen = player.map(|(_, feet)| feet);
+        }
+
+        // Steps are searched from the node the enemy stands on, so keep the old path while
+        // airborne.
+        enemy.repath -= dt;
+        let goal = match enemy.behaviour {
+            EnemyBehaviour::Chase => enemy.last_seen,
+            EnemyBehaviour::Return => Some(foot_point(enemy.home, collider.half_extents)),
+            EnemyBehaviour::Patrol => None,
+        };
+        match goal {
+            Some(goal) if enemy.repath <= 0.0 && state.on_ground => {
+                enemy.repath = REPATH_SECONDS;
+                let reach = JumpReach::for_controller(controller, movement.gravity, map.tile_size);
+                enemy.path = graph
+                    .node_below(&map, feet, 0)
+                    .zip(graph.node_below(&map, goal, GOAL_SEARCH_TILES))
+                    .and_then(|(start, goal)| graph.find_path(start, goal, &reach))
+                    .map(VecDeque::from)
+                    .unwrap_or_default();
+            }
+            Some(_) => {}
+            None => enemy.path.clear(),
+        }
+        let path_step = follow_path(&mut enemy.path, &graph, &map, feet, state.on_ground);
+        let mut wants_jump = false;
+
+        let on_ground = state.on_ground;
+        let blocked = |dir: f32| blocked_ahead(&map, center, collider.half_extents, dir, on_ground);
+        let patrol_axis = enemy.patrol_speed / enemy.chase_speed;
+
+        let axis = match enemy.behaviour {
+            EnemyBehaviour::Patrol => {
+                let dir = match enemy.patrol.get(enemy.patrol_index).copied() {
+                    Some(target) if (target - center.x).abs() <= ARRIVE_DISTANCE => {
+                        enemy.patrol_index = (enemy.patrol_index + 1) % enemy.patrol.len();
+                        0.0
+                    }
+                    Some(target) => (target - center.x).signum(),
+                    None => enemy.facing,
+                };
+
+                if dir != 0.0 && blocked(dir) {
+                    // Skip to the next waypoint (or just turn around) instead of walking into a
+                    // wall or off a ledge.
+                    if !enemy.patrol.is_empty() {
+                        enemy.patrol_index = (enemy.patrol_index + 1) % enemy.patrol.len();
+                    }
+                    enemy.facing = -dir;
+                    0.0
+                } else {
+                    dir * patrol_axis
+                }
+            }
+            EnemyBehaviour::Chase => {
+                if !sees_player {
+                    enemy.unseen += dt;
+                    if enemy.unseen >= LOSE_SIGHT_SECONDS {
+                        enemy.behaviour = EnemyBehaviour::Return;
+                        enemy.last_seen = None;
+                        enemy.repath = 0.0;
+                    }
+                }
+
+                let offset = enemy.last_seen.map_or(0.0, |target| target.x - center.x);
+                let dir = offset.signum();
+                if let Some((dir, jump)) = path_step {
+                    wants_jump = jump;
+                    dir
+                } else if offset.abs() <= ARRIVE_DISTANCE {
+                    0.0
+                } else if blocked(dir) {
+                    // Wait at the edge, still facing the player.
+                    enemy.facing = dir;
+                    0.0
+                } else {
+                    dir
+                }
+            }
+            EnemyBehaviour::Return => {
+                let offset = enemy.home.x - center.x;
+                let dir = offset.signum();
+                if let Some((dir, jump)) = path_step {
+                    wants_jump = jump;
+                    dir * patrol_axis
+                } else if offset.abs() <= ARRIVE_DISTANCE || blocked(dir) {
+                    // Home, or as close as the level allows; resume patrolling from here.
+                    enemy.behaviour = EnemyBehaviour::Patrol;
+                    0.0
+                } else {
+                    dir * patrol_axis
+                }
+            }
+        };
+
+        state.axis = axis;
+        state.wants_jump = wants_jump;
+    }
+}
+
+/// Point just above the bottom of a box, inside the tile the box stands in.
+fn foot_point(center: Vec2, half: Vec2) -> Vec2 {
+    Vec2::new(center.x, center.y - half.y + 1.0)
+}
+
+/// Drops the steps already reached and returns the direction to walk (and whether to jump) for
+/// the next one, or `None` once the path is used up.
+fn follow_path(
+    path: &mut VecDeque<NavStep>,
+    graph: &NavGraph,
+    map: &CollisionMap,
+    feet: Vec2,
+    on_ground: bool,
+) -> Option<(f32, bool)> {
+    if on_ground {
+        if let Some(current) = graph.node_below(map, feet, 0) {
+            if let Some(reached) = path.iter().position(|step| step.tile == current) {
+                path.drain(..=reached);
+            }
+        }
+    }
+
+    let step = *path.front()?;
+    let offset = map.tile_to_world_rect(step.tile).center().x - feet.x;
+    let dir = if offset.abs() <= ARRIVE_DISTANCE {
+        0.0
+    } else {
+        offset.signum()
+    };
+
+    Some((dir, on_ground && step.kind == NavLinkKind::Jump))
+}
+
+/// Whether a box at `center` moving in `dir` is about to hit a wall, or (when standing on the
+/// ground) walk off a ledge.
+fn blocked_ahead(map: &CollisionMap, center: Vec2, half: Vec2, dir: f32, on_ground: bool) -> bool {
+    const PROBE: f32 = 2.0;
+
+    let wall = map.overlaps_solid(center + Vec2::new(dir * PROBE, 0.0), half);
+    let ledge = on_ground
+        && !map.is_solid_at(Vec2::new(
+            center.x + dir * (half.x + PROBE),
+            center.y - half.y - PROBE,
+        ));
+
+    wall || ledge
+}
+
+/// Shoots arrows at the player from enemies with an `arrow_interval` while they chase, as long as
+/// the player is in sight and roughly level with them.
+fn enemy_fire_arrows(
+    time: Res<Time>,
+    map: Res<CollisionMap>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    mut enemies: Query<(&mut Enemy, &Transform, &Collider), Without<Player>>,
+    mut spawns: EventWriter<SpawnProjectile>,
+) {
+    let Ok((player_transform, player_collider)) = player_query.get_single() else {
+        return;
+    };
+    let player = player_collider.center(player_transform.translation.truncate());
+
+    for (mut enemy, transform, collider) in &mut enemies {
+        if enemy.arrow_interval <= 0.0 || enemy.behaviour != EnemyBehaviour::Chase {
+            continue;
+        }
+
+        enemy.arrow_timer = (enemy.arrow_timer - time.delta_seconds()).max(0.0);
+        let center = collider.center(transform.translation.truncate());
+        let level = (player.y - center.y).abs() < collider.half_extents.y;
+        if enemy.arrow_timer > 0.0
+            || !level
+            || center.distance(player) > enemy.sight_range
+            || !map.line_of_sight(center, player)
+        {
+            continue;
+        }
+
+        let dir = (player.x - center.x).signum();
+        let spec = ProjectileSpec::arrow(Vec2::new(dir, 0.0), Faction::Enemy);
+        let position = center + Vec2::new(dir * (collider.half_extents.x + 4.0), 0.0);
+        // Pressed up against a wall the arrow would start inside it; hold the shot until there is
+        // room.
+        if map.overlaps_solid(position, spec.size * 0.5) {
+            continue;
+        }
+
+        enemy.arrow_timer = enemy.arrow_interval;
+        spawns.send(SpawnProjectile { position, spec });
+    }
+}
+
+/// Flips enemy sprites to face the way they are walking (or looking, when standing still).
+fn face_movement(mut enemies: Query<(&mut Enemy, &MovementState, &mut Sprite)>) {
+    for (mut enemy, state, mut sprite) in &mut enemies {
+        if state.axis != 0.0 {
+            enemy.facing = state.axis.signum();
+        }
+        sprite.flip_x = enemy.facing < 0.0;
+    }
+}
+
+/// Damages the player while their collider overlaps an enemy's. Invulnerability after a hit keeps
+/// this from landing every frame.
+fn enemy_contact_damage(
+    player_query: Query<(Entity, &Transform, &Collider), With<Player>>,
+    enemies: Query<(&Enemy, &Transform, &Collider), Without<Player>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let Ok((player, player_transform, player_collider)) = player_query.get_single() else {
+        return;
+    };
+    let player_center = player_collider.center(player_transform.translation.truncate());
+
+    for (enemy, transform, collider) in &enemies {
+        let center = collider.center(transform.translation.truncate());
+        let touching = boxes_overlap(
+            player_center,
+            player_collider.half_extents,
+            center,
+            collider.half_extents,
+        );
+
+        if touching && enemy.contact_damage > 0 {
+            damage.send(DamageEvent {
+                target: player,
+                amount: enemy.contact_damage,
+                source: Some(center),
+                knockback: None,
+            });
+        }
+    }
+}
+
+/// Draws each enemy's line of sight to a player within range alongside the collision debug
+/// outlines: green when clear, otherwise red up to the blocking tile, which is outlined along with
+/// the normal of the face the sight line hit.
+fn draw_sight_debug(
+    map: Res<CollisionMap>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    enemies: Query<(&Enemy, &Transform, &Collider), Without<Player>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((player_transform, player_collider)) = player_query.get_single() else {
+        return;
+    };
+    let player = player_collider.center(player_transform.translation.truncate());
+
+    for (enemy, transform, collider) in &enemies {
+        let center = collider.center(transform.translation.truncate());
+        let distance = center.distance(player);
+        if distance > enemy.sight_range {
+            continue;
+        }
+
+        match map.raycast(center, player - center, distance) {
+            None => gizmos.line_2d(center, player, Color::srgb(0.3, 1.0, 0.4)),
+            Some(hit) => {
+                let blocked = Color::srgb(1.0, 0.3, 0.3);
+                let tile = map.tile_to_world_rect(hit.tile);
+                gizmos.line_2d(center, hit.point, blocked);
+                gizmos.line_2d(
+                    hit.point,
+                    hit.point + hit.normal * tile.half_size(),
+                    blocked,
+                );
+                gizmos.rect_2d(tile.center(), 0.0, tile.size(), blocked);
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Hit points for the player and enemies. Anything that hurts sends a `DamageEvent`; this module
+//! applies it, grants a short window of invulnerability so overlapping hazards don't drain health
+//! every frame, and reports deaths with `Died`.
+//!
+//! A hit from a known position also knocks the target away from it through
+//! `MovementState::apply_knockback`, followed by a short hit-stun.
+//!
+//! Attacks find their targets through `Hurtbox` components and skip targets of the same
+//! `Faction`. When the player dies the current level is reloaded from its entry point. Other
+//! entities are simply despawned.
+
+use bevy::prelude::*;
+
+use crate::animation::AnimationPlayer;
+use crate::camera::CameraShake;
+use crate::collision::boxes_overlap;
+use crate::movement::{Collider, MovementController, MovementState};
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the damage/death events and the systems that resolve them after gameplay effects.
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>().add_event::<Died>().add_systems(
+            Update,
+            (tick_invulnerability, apply_damage, handle_deaths)
+                .chain()
+                .after(GameSet::Effects)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Hit points. `invulnerability` is how long (in seconds) further damage is ignored after a hit.
+#[derive(Component, Clone, Debug)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+    pub invulnerability: f32,
+    /// Time left in the current invulnerability window.
+    remaining_invulnerability: f32,
+}
+
+impl Health {
+    pub fn new(max: i32) -> Self {
+        Self {
+            current: max,
+            max,
+            invulnerability: 0.0,
+            remaining_invulnerability: 0.0,
+        }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.invulnerability = seconds;
+        self
+    }
+
+    /// Restores up to `amount` hit points without going over `max`.
+    pub fn heal(&mut self, amount: i32) {
+        self.current = (self.current + amount.max(0)).min(self.max);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.remaining_invulnerability > 0.0
+    }
+}
+
+/// Side an entity fights on. Attacks only land on hurtboxes of a different faction.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Faction {
+    Player,
+    Enemy,
+    /// Hazards such as traps, which hurt everyone.
+    Neutral,
+}
+
+impl Faction {
+    pub fn is_hostile_to(self, other: Faction) -> bool {
+        self != other || self == Faction::Neutral
+    }
+}
+
+/// Area in which an entity can be hit by attacks. Usually the same box as its `Collider`, placed
+/// the same way relative to the entity's translation.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Hurtbox {
+    pub half_extents: Vec2,
+    pub offset: Vec2,
+}
+
+impl Hurtbox {
+    pub fn from_collider(collider: &Collider) -> Self {
+        Self {
+            half_extents: collider.half_extents,
+            offset: collider.offset,
+        }
+    }
+
+    /// Whether this hurtbox (on an entity at `position`) overlaps the box at `center`.
+    pub fn overlaps(&self, position: Vec2, center: Vec2, half_extents: Vec2) -> bool {
+        boxes_overlap(position + self.offset, self.half_extents, center, half_extents)
+    }
+}
+
+/// Request to hurt `target`. `source` is the world position the hit came from, if any; hits with
+/// a source knock the target away from it, with `knockback` overriding the target's own
+/// `MovementController::knockback`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: i32,
+    pub source: Option<Vec2>,
+    pub knockback: Option<Vec2>,
+}
+
+/// Sent once when an entity's health reaches zero.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Died {
+    pub entity: Entity,
+}
+
+fn tick_invulnerability(time: Res<Time>, mut query: Query<&mut Health>) {
+    let dt = time.delta_seconds();
+    for mut health in &mut query {
+        if health.is_invulnerable() {
+            health.remaining_invulnerability = (health.remaining_invulnerability - dt).max(0.0);
+        }
+    }
+}
+
+/// Subtracts damage from targets that are alive and not currently invulnerable, plays their hurt
+/// clip, knocks them back, and shakes the camera when the player is hit.
+#[allow(clippy::type_complexity)]
+fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut targets: Query<(
+        &mut Health,
+        &Transform,
+        Option<&mut AnimationPlayer>,
+        Option<(&mut MovementState, &MovementController)>,
+        Has<Player>,
+    )>,
+    mut shakes: EventWriter<CameraShake>,
+    mut deaths: EventWriter<Died>,
+) {
+    for event in events.read() {
+        let Ok((mut health, transform, animation, movement, is_player)) =
+            targets.get_mut(event.target)
+        else {
+            continue;
+        };
+        if health.is_dead() || health.is_invulnerable() || event.amount <= 0 {
+            continue;
+        }
+
+        health.current = (health.current - event.amount).max(0);
+        health.remaining_invulnerability = health.invulnerability;
+
+        if let Some(mut animation) = animation {
+            animation.play_hurt();
+        }
+        if let (Some((mut state, controller)), Some(source)) = (movement, event.source) {
+            let away = if transform.translation.x < source.x { -1.0 } else { 1.0 };
+            let knockback = event.knockback.unwrap_or(controller.knockback);
+            state.apply_knockback(
+                Vec2::new(knockback.x * away, knockback.y),
+                controller.hit_stun,
+            );
+        }
+        if is_player {
+            shakes.send(CameraShake {
+                intensity: 0.4,
+                duration: 0.25,
+            });
+        }
+        if health.is_dead() {
+            deaths.send(Died {
+                entity: event.target,
+            });
+        }
+    }
+}
+
+/// Reloads the level when the player dies and despawns anything else that did.
+fn handle_deaths(
+    mut commands: Commands,
+    mut deaths: EventReader<Died>,
+    players: Query<(), With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for Died { entity } in deaths.read() {
+        if players.contains(*entity) {
+            info!("Player died; reloading level");
+            next_state.set(GameState::Loading);
+        } else if let Some(entity) = commands.get_entity(*entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
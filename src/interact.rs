@@ -0,0 +1,74 @@
+//! The `E` interaction. Anything the player can operate (doors, levers) carries an
+//! `Interactable`; when `E` is pressed, only the closest one within reach is sent an `Interact`
+//! event, so standing between a door and a lever never operates both. Presses are ignored while a
+//! camera script is playing.
+
+use bevy::prelude::*;
+
+use crate::cinematic::CameraDirector;
+use crate::collision::boxes_overlap;
+use crate::movement::Collider;
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers the interaction event and the system that picks its target.
+pub struct InteractPlugin;
+
+impl Plugin for InteractPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Interact>().add_systems(
+            Update,
+            pick_interaction
+                .in_set(GameSet::Input)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Box, centred on the entity, that the player can operate from up to `reach` pixels away.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Interactable {
+    pub half_extents: Vec2,
+    pub reach: f32,
+}
+
+/// Sent to the interactable the player just used.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Interact {
+    pub target: Entity,
+}
+
+/// On an `E` press, sends `Interact` to the interactable whose box is closest to the player's
+/// collider, among those within their reach.
+pub fn pick_interaction(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    director: Res<CameraDirector>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    targets: Query<(Entity, &GlobalTransform, &Interactable)>,
+    mut interact: EventWriter<Interact>,
+) {
+    if director.is_playing() || !keyboard.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    let Ok((player_transform, collider)) = player_query.get_single() else {
+        return;
+    };
+    let player = collider.center(player_transform.translation.truncate());
+
+    let nearest = targets
+        .iter()
+        .filter_map(|(entity, transform, target)| {
+            let center = transform.translation().truncate();
+            let reach = collider.half_extents + target.reach;
+            boxes_overlap(player, reach, center, target.half_extents).then(|| {
+                // Distance between the two boxes; zero when they overlap.
+                let gap = (player - center).abs() - (collider.half_extents + target.half_extents);
+                (entity, gap.max(Vec2::ZERO).length())
+            })
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    if let Some((target, _)) = nearest {
+        interact.send(Interact { target });
+    }
+}
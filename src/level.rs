@@ -1,26 +1,31 @@
-//! LDtk level orchestration: loads project data, tracks metadata, and aligns the camera.
+//! LDtk level orchestration: loads project data and tracks level metadata.
 //!
 //! All persistent data is stored in Bevy resources (`LevelConfig`, `LevelAssets`). Rust's ownership
 //! system ensures these allocations are freed when the app terminates; during runtime, they are
 //! shared immutably or mutably through the ECS borrow rules.
 
+use std::collections::{HashMap, HashSet};
+
 use bevy::asset::LoadState;
 use bevy::math::IVec2;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_ecs_ldtk::utils::ldtk_pixel_coords_to_translation;
 use bevy_ecs_ldtk::LevelIid;
 
+use crate::campaign::{campaign_settled, Campaign, CampaignLevel, CampaignManifest, CAMPAIGN_PATH};
+use crate::loading::LoadingProgress;
 use crate::state::GameState;
 
-/// Registers LDtk asset plumbing and camera synchronisation systems.
+/// Registers LDtk asset plumbing and level metadata systems.
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(LevelConfig::default())
+        app.init_resource::<LevelConfig>()
             .init_resource::<LevelAssets>()
+            .init_resource::<LevelHistory>()
+            .init_resource::<LevelLoadFailure>()
             .insert_resource(LevelSelection::index(0))
             .insert_resource(LdtkSettings {
                 level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
@@ -30,28 +35,43 @@ impl Plugin for LevelPlugin {
                 ..default()
             })
             .add_plugins(LdtkPlugin)
-            .add_systems(OnEnter(GameState::Loading), spawn_world)
+            .add_systems(OnEnter(GameState::Loading), despawn_world)
+            .add_systems(OnEnter(GameState::LoadError), despawn_world)
             .add_systems(
                 Update,
-                monitor_level_loading.run_if(in_state(GameState::Loading)),
+                (
+                    spawn_world
+                        .before(crate::loading::update_loading_progress)
+                        .run_if(campaign_settled),
+                    monitor_level_loading.after(crate::loading::update_loading_progress),
+                )
+                    .run_if(in_state(GameState::Loading)),
             )
             .add_systems(
-                PostUpdate,
-                (
-                    cache_level_transform,
-                    sync_level_spatial.after(cache_level_transform),
-                ),
-            );
+                Update,
+                refresh_level_on_reload
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Paused))),
+            )
+            .add_systems(PostUpdate, cache_level_transform);
     }
 }
 
+/// Spawn offset used when neither the campaign manifest nor the configuration names an entry.
+pub const DEFAULT_SPAWN_OFFSET: Vec2 = Vec2::new(340.0, 340.0);
+
 /// Runtime-tweakable configuration describing which LDtk project + level to load, how to shift it
 /// in world space, and how large the tiles/camera zoom are. Cloned when other systems need read-only
-/// access; cloning is cheap because it only copies primitive values.
+/// access; cloning is cheap because it only copies a few small values.
+///
+/// Which level to load is normally decided by the campaign manifest: `level_id` names a manifest
+/// entry, and an empty `project_path` means "start of the campaign". `project_path`/`start_level`
+/// are filled in from the manifest when the world is spawned.
 #[derive(Resource, Clone)]
 pub struct LevelConfig {
+    pub level_id: Option<String>,
     pub project_path: String,
     pub start_level: Option<String>,
+    pub entry: Option<String>,
     pub frame_shift: Vec2,
     pub tile_size: f32,
     pub camera_zoom: f32,
@@ -60,8 +80,10 @@ pub struct LevelConfig {
 impl Default for LevelConfig {
     fn default() -> Self {
         Self {
-            project_path: "levels/test_map_1_newres.ldtk".to_owned(),
-            start_level: Some("Level_0".to_owned()),
+            level_id: None,
+            project_path: String::new(),
+            start_level: None,
+            entry: None,
             frame_shift: Vec2::ZERO,
             tile_size: 16.0,
             camera_zoom: 0.5,
@@ -69,6 +91,37 @@ impl Default for LevelConfig {
     }
 }
 
+impl LevelConfig {
+    /// Finds the manifest level this configuration refers to. An explicit `level_id` wins, then a
+    /// matching project path; an empty configuration resolves to the campaign's start level.
+    pub fn campaign_level<'a>(&self, manifest: &'a CampaignManifest) -> Option<&'a CampaignLevel> {
+        if let Some(id) = &self.level_id {
+            return manifest.level(id);
+        }
+
+        if self.project_path.is_empty() {
+            return manifest.start_level();
+        }
+
+        manifest.level_for_project(&self.project_path, self.start_level.as_deref())
+    }
+
+    /// Points the configuration at a campaign level. The entry is left untouched so callers can
+    /// pick one explicitly.
+    pub fn select_campaign_level(&mut self, level: &CampaignLevel) {
+        self.level_id = Some(level.id.clone());
+        self.project_path = level.project.clone();
+        self.start_level = Some(level.level.clone());
+    }
+
+    /// Whether both configurations load the same level, ignoring entry and presentation settings.
+    pub fn same_level(&self, other: &LevelConfig) -> bool {
+        self.level_id == other.level_id
+            && self.project_path == other.project_path
+            && self.start_level == other.start_level
+    }
+}
+
 /// Mirror of the currently loaded level's metadata. Optional fields become `Some` once assets are
 /// available. Other systems (camera/collision) read this without owning the LDtk structures.
 #[derive(Resource, Default)]
@@ -80,6 +133,86 @@ pub struct LevelAssets {
     pub level_origin: Option<Vec2>,
     pub level_size: Option<Vec2>,
     pub level_center: Option<Vec2>,
+    pub display_name: Option<String>,
+    pub spawn_offset: Option<Vec2>,
+}
+
+/// Entity IIDs grouped by the IID of the level they are in. Used to remember what happened to
+/// individual entities (picked up, opened) across level loads.
+#[derive(Default, Debug)]
+pub struct LevelIids {
+    by_level: HashMap<String, HashSet<String>>,
+}
+
+impl LevelIids {
+    pub fn contains(&self, level_iid: &str, entity_iid: &str) -> bool {
+        self.by_level
+            .get(level_iid)
+            .is_some_and(|entities| entities.contains(entity_iid))
+    }
+
+    pub fn insert(&mut self, level_iid: &str, entity_iid: &str) {
+        self.by_level
+            .entry(level_iid.to_string())
+            .or_default()
+            .insert(entity_iid.to_string());
+    }
+}
+
+/// Remembers which configuration the session started with and which level last finished loading.
+/// A failed transition restores `last_loaded` so the player is never stranded in a broken world.
+#[derive(Resource, Default)]
+pub struct LevelHistory {
+    pub start: Option<LevelConfig>,
+    pub last_loaded: Option<LevelConfig>,
+}
+
+/// Details about the most recent LDtk project load failure. Populated whenever a load fails; the
+/// error screen reads it to describe the problem and to retry the failed configuration, and the
+/// HUD reads it to report a failure that was recovered by returning to the previous level.
+#[derive(Resource, Default)]
+pub struct LevelLoadFailure {
+    pub attempted: Option<LevelConfig>,
+    pub error: String,
+    /// Set when the failure was recovered by rolling back; cleared once the notice is shown.
+    pub rolled_back: bool,
+}
+
+impl LevelLoadFailure {
+    /// Records a failure for `config` and rolls it back to the last level that loaded successfully.
+    /// Returns `false` when there is nothing to roll back to (no level has loaded yet, or the
+    /// failing level is the previous one), in which case the caller shows the error screen.
+    pub fn record(
+        &mut self,
+        config: &mut LevelConfig,
+        error: String,
+        history: &LevelHistory,
+    ) -> bool {
+        self.attempted = Some(config.clone());
+        self.error = error;
+
+        match &history.last_loaded {
+            Some(previous) if !previous.same_level(config) => {
+                warn!("Returning to the previous level after the failed load");
+                *config = previous.clone();
+                self.rolled_back = true;
+                true
+            }
+            _ => {
+                self.rolled_back = false;
+                false
+            }
+        }
+    }
+
+    /// Path of the project that failed to load, for display purposes.
+    pub fn path(&self) -> &str {
+        self.attempted
+            .as_ref()
+            .map(|config| config.project_path.as_str())
+            .filter(|path| !path.is_empty())
+            .unwrap_or("<unknown>")
+    }
 }
 
 /// Marker on the LDtk world entity so we can despawn it before loading another level, avoiding
@@ -87,20 +220,74 @@ pub struct LevelAssets {
 #[derive(Component)]
 pub struct LevelRoot;
 
+/// Resolves `LevelConfig` against the campaign manifest and spawns the LDtk world. Runs every frame
+/// in `Loading` once the manifest has settled, but only acts while no project has been requested.
+#[allow(clippy::too_many_arguments)]
 fn spawn_world(
     mut commands: Commands,
-    world: Query<Entity, With<LevelRoot>>,
     asset_server: Res<AssetServer>,
-    config: Res<LevelConfig>,
+    campaign: Campaign,
+    mut config: ResMut<LevelConfig>,
     mut level_assets: ResMut<LevelAssets>,
     mut selection: ResMut<LevelSelection>,
+    mut history: ResMut<LevelHistory>,
+    mut failure: ResMut<LevelLoadFailure>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    // Despawn any previously spawned LDtk world so we don't leak entities or memory. Bevy handles
-    // recursive child destruction when `despawn_recursive` is used.
-    for entity in &world {
-        commands.entity(entity).despawn_recursive();
+    if level_assets.project.is_some() {
+        return;
+    }
+
+    if history.start.is_none() {
+        history.start = Some(config.clone());
+    }
+
+    let campaign_level = campaign
+        .manifest()
+        .and_then(|manifest| config.campaign_level(manifest))
+        .cloned();
+
+    if let Some(level) = &campaign_level {
+        config.select_campaign_level(level);
+        info!("Loading campaign level '{}'", level.display_name);
+    } else if config.project_path.is_empty() {
+        let error = match config.level_id.as_deref() {
+            Some(id) if campaign.manifest().is_some() => {
+                format!("Campaign manifest '{}' has no level '{}'", CAMPAIGN_PATH, id)
+            }
+            _ => format!("Campaign manifest '{}' is unavailable", CAMPAIGN_PATH),
+        };
+        warn!("{}", error);
+        // On a rollback nothing has been spawned yet, so the next frame simply loads the previous
+        // level instead.
+        if !failure.record(&mut config, error, &history) {
+            next_state.set(GameState::LoadError);
+        }
+        return;
     }
 
+    // A project that failed earlier stays cached, still failed, until every handle to it has been
+    // dropped. `despawn_world` releases ours before the error screen appears; wait here until the
+    // asset server has forgotten it so a retry reads the file again instead of getting the stale
+    // failure back.
+    let stale_failure = asset_server
+        .get_path_id(&config.project_path)
+        .and_then(|id| asset_server.get_load_state(id))
+        .is_some_and(|state| matches!(state, LoadState::Failed(_)));
+    if stale_failure {
+        return;
+    }
+
+    level_assets.display_name = campaign_level
+        .as_ref()
+        .map(|level| level.display_name.clone());
+    level_assets.spawn_offset = Some(
+        campaign_level
+            .as_ref()
+            .and_then(|level| level.entry_position(config.entry.as_deref()))
+            .unwrap_or(DEFAULT_SPAWN_OFFSET),
+    );
+
     let project_handle: Handle<LdtkProject> = asset_server.load(config.project_path.clone());
     level_assets.project = Some(project_handle.clone());
     level_assets.project_path = Some(config.project_path.clone());
@@ -124,11 +311,17 @@ fn spawn_world(
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn monitor_level_loading(
+    mut commands: Commands,
     asset_server: Res<AssetServer>,
+    world: Query<Entity, With<LevelRoot>>,
     mut level_assets: ResMut<LevelAssets>,
     projects: Res<Assets<LdtkProject>>,
-    config: Res<LevelConfig>,
+    mut config: ResMut<LevelConfig>,
+    mut history: ResMut<LevelHistory>,
+    mut failure: ResMut<LevelLoadFailure>,
+    progress: Res<LoadingProgress>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     let Some(project_handle) = level_assets.project.as_ref() else {
@@ -137,54 +330,122 @@ fn monitor_level_loading(
 
     match asset_server.get_load_state(project_handle.id()) {
         Some(LoadState::Loaded) => {
+            // The project JSON alone isn't enough: hold here until tilesets and audio have
+            // settled too, which matters on the web build where each asset is a separate request.
+            if !progress.is_complete() {
+                return;
+            }
+
             // Once the project JSON + tilesets are loaded we can read metadata to cache sizes.
             if let Some(project) = projects.get(project_handle) {
-                let level_data = config
-                    .start_level
-                    .as_ref()
-                    .and_then(|identifier| {
-                        project
-                            .json_data()
-                            .levels
-                            .iter()
-                            .find(|level| &level.identifier == identifier)
-                    })
-                    .or_else(|| project.json_data().levels.first());
-
-                if let Some(level) = level_data {
-                    let origin = ldtk_pixel_coords_to_translation(
-                        IVec2::new(level.world_x, level.world_y + level.px_hei),
-                        0,
-                    );
-                    let size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
-                    let center = Vec2::new(
-                        level.world_x as f32 + size.x * 0.5,
-                        -(level.world_y as f32) - size.y * 0.5,
-                    );
-
-                    level_assets.level_identifier = Some(level.identifier.clone());
-                    level_assets.level_iid = Some(level.iid.clone());
-                    level_assets.level_origin = Some(origin);
-                    level_assets.level_size = Some(size);
-                    level_assets.level_center = Some(center);
-                }
+                cache_level_metadata(project, &config, &mut level_assets);
             }
 
+            history.last_loaded = Some(config.clone());
             next_state.set(GameState::Playing);
         }
-        Some(LoadState::Failed(_)) => {
+        Some(LoadState::Failed(error)) => {
             let path = level_assets.project_path.as_deref().unwrap_or("<unknown>");
-            warn!(
-                "Unable to load LDtk project at '{}'; continuing with placeholder state.",
-                path
-            );
-            next_state.set(GameState::Playing);
+            warn!("Unable to load LDtk project at '{}': {}", path, error);
+
+            // Roll the configuration back to the last level that loaded successfully and load it
+            // again straight away, so a failed transition never strands the player. The failed
+            // target is kept separately for the retry option on the error screen, which is only
+            // shown when there is nothing to go back to.
+            if failure.record(&mut config, error.to_string(), &history) {
+                clear_world(&mut commands, &world, &mut level_assets);
+            } else {
+                next_state.set(GameState::LoadError);
+            }
         }
         _ => {}
     }
 }
 
-fn cache_level_transform(
+/// Copies the selected level's identifier, IID, and spatial extents out of the project JSON. The
+/// origin is the level's bottom-left corner in Bevy coordinates, including `frame_shift`.
+fn cache_level_metadata(project: &LdtkProject, config: &LevelConfig, level_assets: &mut LevelAssets) {
+    let level_data = config
+        .start_level
+        .as_ref()
+        .and_then(|identifier| {
+            project
+                .json_data()
+                .levels
+                .iter()
+                .find(|level| &level.identifier == identifier)
+        })
+        .or_else(|| project.json_data().levels.first());
+
+    let Some(level) = level_data else {
+        return;
+    };
+
+    let origin = ldtk_pixel_coords_to_translation(
+        IVec2::new(level.world_x, level.world_y + level.px_hei),
+        0,
+    ) + config.frame_shift;
+    let size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+
+    level_assets.level_identifier = Some(level.identifier.clone());
+    level_assets.level_iid = Some(level.iid.clone());
+    level_assets.level_origin = Some(origin);
+    level_assets.level_size = Some(size);
+    level_assets.level_center = Some(origin + size * 0.5);
+}
+
+/// Handles edits saved from LDtk while playing. bevy_ecs_ldtk already respawns the world when the
+/// project asset is modified; this re-reads the level metadata so the collision rebuild triggered
+/// by the respawn (and the camera bounds) use the new dimensions.
+fn refresh_level_on_reload(
+    mut events: EventReader<AssetEvent<LdtkProject>>,
+    projects: Res<Assets<LdtkProject>>,
+    config: Res<LevelConfig>,
+    mut level_assets: ResMut<LevelAssets>,
+) {
+    let Some(project_id) = level_assets.project.as_ref().map(|handle| handle.id()) else {
+        events.clear();
+        return;
+    };
+
+    if !events.read().any(|event| event.is_modified(project_id)) {
+        return;
+    }
+
+    if let Some(project) = projects.get(project_id) {
+        cache_level_metadata(project, &config, &mut level_assets);
+        info!(
+            "Hot-reloaded LDtk project '{}'",
+            level_assets.project_path.as_deref().unwrap_or("<unknown>")
+        );
+    }
+}
+
+/// Despawns the LDtk world and forgets its project handle. Runs when entering `Loading`, so
+/// `spawn_world` requests a fresh project, and after a failed load, so a stale bundle doesn't
+/// linger behind the error screen or the menu. Bevy handles recursive child destruction when
+/// `despawn_recursive` is used.
+fn despawn_world(
+    mut commands: Commands,
+    world: Query<Entity, With<LevelRoot>>,
+    mut level_assets: ResMut<LevelAssets>,
+) {
+    clear_world(&mut commands, &world, &mut level_assets);
+}
+
+fn clear_world(
+    commands: &mut Commands,
+    world: &Query<Entity, With<LevelRoot>>,
+    level_assets: &mut LevelAssets,
+) {
+    for entity in world {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    level_assets.project = None;
+}
+
+pub fn cache_level_transform(
     mut level_assets: ResMut<LevelAssets>,
     level_query: Query<(&GlobalTransform, &LevelIid), Added<LevelIid>>,
 ) {
@@ -207,32 +468,3 @@ fn cache_level_transform(
         }
     }
 }
-
-pub fn sync_level_spatial(
-    level_assets: Res<LevelAssets>,
-    config: Res<LevelConfig>,
-    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-) {
-    if !level_assets.is_changed() {
-        return;
-    }
-
-    let (Some(center), Some(_size)) = (level_assets.level_center, level_assets.level_size) else {
-        return;
-    };
-
-    let Ok((mut camera_transform, mut projection)) = camera_query.get_single_mut() else {
-        return;
-    };
-
-    if let Ok(window) = windows.get_single() {
-        let _window_size = window.resolution.size();
-        projection.scale = config.camera_zoom.max(0.0001);
-    }
-
-    // Move the camera to the cached center. Z is left untouched so other systems (e.g., follow
-    // smoothing) can maintain depth. Transform writes are staged by Bevy to avoid data races.
-    camera_transform.translation.x = center.x;
-    camera_transform.translation.y = center.y;
-}
@@ -0,0 +1,217 @@
+//! Loading screen and asset readiness tracking. While the game is in `GameState::Loading`, the
+//! campaign manifest, the LDtk project, its tilesets, the player's animation clips, and every
+//! `AudioHandles` entry are polled each frame. The level module only advances to `Playing` once
+//! all of them have settled.
+//!
+//! Tracking only inspects asset ids; no extra strong handles are cloned, so this module never
+//! extends the lifetime of an asset.
+
+use bevy::asset::{LoadState, UntypedAssetId};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::animation::PlayerAnimations;
+use crate::audio::AudioHandles;
+use crate::campaign::Campaign;
+use crate::level::LevelAssets;
+use crate::state::GameState;
+
+/// Registers the progress resource plus the loading screen spawn/update/despawn systems.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingProgress>()
+            .add_systems(
+                OnEnter(GameState::Loading),
+                (reset_loading_progress, spawn_loading_screen),
+            )
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen)
+            .add_systems(
+                Update,
+                (
+                    update_loading_progress,
+                    update_loading_screen.after(update_loading_progress),
+                )
+                    .run_if(in_state(GameState::Loading)),
+            );
+    }
+}
+
+/// How many tracked assets have settled (loaded or failed) out of those known so far. Tilesets are
+/// only discovered once the project JSON has been parsed, so `total` can grow while loading.
+#[derive(Resource, Default)]
+pub struct LoadingProgress {
+    pub settled: usize,
+    pub total: usize,
+    pub stage: Option<&'static str>,
+}
+
+impl LoadingProgress {
+    /// Fraction in `0.0..=1.0` suitable for driving a progress bar.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.settled as f32 / self.total as f32
+        }
+    }
+
+    /// True once every tracked asset has either loaded or failed. Failed optional assets (missing
+    /// audio placeholders, broken tileset paths) must not hold the game on the loading screen;
+    /// Bevy already logs their errors.
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.settled >= self.total
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingBarFill;
+
+#[derive(Component)]
+struct LoadingLabel;
+
+fn reset_loading_progress(mut progress: ResMut<LoadingProgress>) {
+    *progress = LoadingProgress::default();
+}
+
+/// Collects the ids of everything the level needs and counts how many have settled. Runs before
+/// `monitor_level_loading` so the level module sees this frame's numbers.
+pub fn update_loading_progress(
+    asset_server: Res<AssetServer>,
+    level_assets: Res<LevelAssets>,
+    projects: Res<Assets<LdtkProject>>,
+    audio: Res<AudioHandles>,
+    animations: Res<PlayerAnimations>,
+    campaign: Campaign,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    let mut tracked: Vec<(&'static str, UntypedAssetId)> =
+        vec![("campaign", campaign.id().untyped())];
+
+    if let Some(project_handle) = level_assets.project.as_ref() {
+        tracked.push(("level data", project_handle.id().untyped()));
+
+        if let Some(project) = projects.get(project_handle) {
+            tracked.extend(
+                project
+                    .tileset_map()
+                    .values()
+                    .map(|handle| ("tilesets", handle.id().untyped())),
+            );
+        }
+    }
+
+    tracked.push(("animations", animations.0.id().untyped()));
+    tracked.extend(audio.iter().map(|handle| ("audio", handle.id().untyped())));
+
+    let mut stage = None;
+    let mut settled = 0;
+    for (label, id) in &tracked {
+        match asset_server.get_load_state(*id) {
+            Some(LoadState::Loaded | LoadState::Failed(_)) => settled += 1,
+            _ => {
+                stage.get_or_insert(*label);
+            }
+        }
+    }
+
+    progress.settled = settled;
+    progress.total = tracked.len();
+    progress.stage = stage;
+}
+
+/// Spawns a full-screen node with a caption and a progress bar whose fill width tracks
+/// `LoadingProgress::fraction`.
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            LoadingScreen,
+            Name::new("LoadingScreen"),
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgba(0.02, 0.02, 0.04, 1.0)),
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LoadingLabel,
+                TextBundle::from_section(
+                    "Loading...",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::srgba(0.9, 0.9, 0.9, 1.0),
+                        ..default()
+                    },
+                ),
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    background_color: BackgroundColor(Color::srgba(0.2, 0.2, 0.25, 1.0)),
+                    style: Style {
+                        width: Val::Px(320.0),
+                        height: Val::Px(12.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        LoadingBarFill,
+                        NodeBundle {
+                            background_color: BackgroundColor(Color::srgba(0.85, 0.75, 0.4, 1.0)),
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn update_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut fill_query: Query<&mut Style, With<LoadingBarFill>>,
+    mut label_query: Query<&mut Text, With<LoadingLabel>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for mut style in &mut fill_query {
+        style.width = Val::Percent(progress.fraction() * 100.0);
+    }
+
+    for mut text in &mut label_query {
+        text.sections[0].value = match progress.stage {
+            Some(stage) => format!(
+                "Loading {}... ({}/{})",
+                stage, progress.settled, progress.total
+            ),
+            None => "Loading...".to_owned(),
+        };
+    }
+}
+
+/// Removes the loading screen on state exit.
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -4,13 +4,31 @@
 //! keeps long-lived singletons (plugins, resources) alive for the duration of the app. This file
 //! wires those pieces together and defers to the `DungeonPlatformerPlugin` defined in `app.rs`.
 
+mod animation;
 mod app;
 mod audio;
+mod breakable;
 mod camera;
+mod campaign;
+mod cinematic;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+mod collectible;
 mod collision;
+mod combat;
+mod door;
+mod enemy;
+mod health;
+mod interact;
 mod level;
+mod loading;
 mod movement;
+mod navigation;
 mod player;
+mod projectile;
+mod pushable;
+mod puzzle;
+mod render;
 mod state;
 mod transition;
 mod ui;
@@ -20,6 +38,7 @@ mod wasm;
 
 use app::DungeonPlatformerPlugin;
 use bevy::asset::AssetPlugin;
+use bevy::audio::{AudioPlugin, Volume};
 use bevy::prelude::*;
 use bevy::render::texture::ImagePlugin;
 use bevy::window::{Window, WindowResizeConstraints, WindowResolution};
@@ -28,11 +47,17 @@ fn main() {
     #[cfg(all(target_arch = "wasm32", feature = "web"))]
     wasm::set_panic_hook();
 
+    // Native builds accept command-line overrides for the starting level, window, and debug aids.
+    // They are parsed before anything else so invalid arguments fail fast.
+    #[cfg(not(target_arch = "wasm32"))]
+    let cli = cli::CliOptions::from_env();
+
     // The window resource drives swap-chain configuration. We keep the logical resolution at
     // 1280×720 so that LDtk's pixel grid maps 1:1 to Bevy world units. Resizing is enabled, but
     // constraints prevent collapsing the window to unusable sizes. Bevy handles the underlying
     // OS resources, so no manual deallocation is necessary.
-    let primary_window = Window {
+    #[allow(unused_mut)]
+    let mut primary_window = Window {
         title: "Dungeon Platformer".to_string(),
         resolution: WindowResolution::new(1280.0, 720.0),
         resizable: true,
@@ -47,6 +72,9 @@ fn main() {
         ..default()
     };
 
+    #[cfg(not(target_arch = "wasm32"))]
+    cli.apply_to_window(&mut primary_window);
+
     // `DefaultPlugins` spins up rendering, input, audio, etc. We override pieces that matter for
     // this project: nearest-neighbor sampling for crisp pixels, and asset settings for desktop vs
     // web. Bevy keeps plugin instances in an internal registry, so we simply compose and hand them
@@ -65,6 +93,15 @@ fn main() {
             watch_for_changes_override: Some(true),
             ..default()
         });
+
+        if cli.mute {
+            default_plugins = default_plugins.set(AudioPlugin {
+                global_volume: GlobalVolume {
+                    volume: Volume::ZERO,
+                },
+                ..default()
+            });
+        }
     }
 
     #[cfg(all(target_arch = "wasm32", feature = "web"))]
@@ -78,9 +115,29 @@ fn main() {
 
     // `App::new()` allocates the ECS world and schedule. Plugins + the clear color describe
     // startup state; once `run()` is called, Bevy drives the main loop until the process exits.
-    App::new()
-        .insert_resource(ClearColor(Color::srgb(0.02, 0.02, 0.04)))
-        .add_plugins(default_plugins)
-        .add_plugins(DungeonPlatformerPlugin)
-        .run();
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::srgb(0.02, 0.02, 0.04)))
+        .add_plugins(default_plugins);
+
+    // Resources inserted before `DungeonPlatformerPlugin` take precedence over the plugins'
+    // `init_resource` defaults.
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut level_config = level::LevelConfig::default();
+        cli.apply_to_level_config(&mut level_config);
+        app.insert_resource(level_config)
+            .insert_resource(collision::CollisionDebug {
+                enabled: cli.debug_collision,
+            })
+            .insert_resource(camera::CameraSettings {
+                screen_shake: !cli.no_screen_shake,
+                ..default()
+            })
+            .insert_resource(render::RenderSettings {
+                pixel_perfect: cli.pixel_perfect,
+                ..default()
+            });
+    }
+
+    app.add_plugins(DungeonPlatformerPlugin).run();
 }
@@ -1,5 +1,9 @@
 //! Player movement systems: input sampling, kinematic integration, and tile collision resolution.
 //!
+//! `Pushable` bodies (crates) also take part: they fall and stack under gravity before characters
+//! move, landing on characters as well as on each other; characters collide with and stand on them,
+//! and the player shoves them sideways along with anything in their way or stacked on top.
+//!
 //! The high numeric values used here reflect the world-unit scale (1 unit = 1 LDtk pixel). Because
 //! sprites are small, accelerations and gravity must be large to achieve responsive motion. No
 //! manual memory management is needed—the ECS owns component data.
@@ -7,7 +11,11 @@
 use bevy::input::keyboard::KeyCode;
 use bevy::prelude::*;
 
+use crate::camera::CameraShake;
+use crate::cinematic::CameraDirector;
 use crate::collision::CollisionMap;
+use crate::health::Hurtbox;
+use crate::player::Player;
 use crate::state::{GameSet, GameState};
 
 /// Registers movement-related systems. The plugin itself carries no runtime state.
@@ -19,7 +27,13 @@ impl Plugin for MovementPlugin {
             Update,
             (
                 read_player_input.in_set(GameSet::Input),
-                apply_kinematics.in_set(GameSet::Movement),
+                (
+                    apply_pushable_physics,
+                    apply_kinematics,
+                    sync_stance_hurtboxes,
+                )
+                    .chain()
+                    .in_set(GameSet::Movement),
             )
                 .run_if(in_state(GameState::Playing)),
         );
@@ -32,6 +46,8 @@ impl Plugin for MovementPlugin {
 pub struct MovementSettings {
     pub gravity: f32,
     pub terminal_velocity: f32,
+    /// Landing faster than this (downward speed) shakes the camera, scaled up to terminal velocity.
+    pub hard_landing_speed: f32,
 }
 
 impl Default for MovementSettings {
@@ -39,6 +55,7 @@ impl Default for MovementSettings {
         Self {
             gravity: 1150.0,
             terminal_velocity: -1800.0,
+            hard_landing_speed: 900.0,
         }
     }
 }
@@ -48,36 +65,80 @@ impl Default for MovementSettings {
 #[derive(Component, Default, Deref, DerefMut)]
 pub struct Velocity(pub Vec2);
 
-/// Controller tuning specific to the player. Acceleration values are large to hit max speed in a
-/// fraction of a second, keeping movement snappy given the pixel-scale world units.
+/// Controller tuning for a kinematic character (the player or a walking enemy). Acceleration values
+/// are large to hit max speed in a fraction of a second, keeping movement snappy given the
+/// pixel-scale world units.
 #[derive(Component)]
-pub struct PlayerController {
+pub struct MovementController {
     pub ground_accel: f32,
     pub air_accel: f32,
     pub ground_max_speed: f32,
     pub air_max_speed: f32,
+    pub crouch_max_speed: f32,
     pub jump_strength: f32,
+    /// Launch velocity when hit without an attack-specific knockback; `x` points away from the
+    /// source of the damage.
+    pub knockback: Vec2,
+    /// Seconds input is ignored after being knocked back.
+    pub hit_stun: f32,
 }
 
-impl Default for PlayerController {
+impl Default for MovementController {
     fn default() -> Self {
         Self {
             ground_accel: 1600.0,
             air_accel: 1200.0,
             ground_max_speed: 325.0,
             air_max_speed: 275.0,
+            crouch_max_speed: 110.0,
             jump_strength: 480.0,
+            knockback: Vec2::new(220.0, 200.0),
+            hit_stun: 0.35,
         }
     }
 }
 
 /// Per-entity movement state flags. `axis` stores the last input direction so the kinematics system
-/// can ramp velocity toward the desired target after the input sampling stage.
+/// can ramp velocity toward the desired target after the input sampling stage. `wall_contact` is
+/// the side (-1.0 left, 1.0 right) of a wall the entity ran into this frame, or 0.0.
+/// `wants_crouch` is held input; `stance` is what the entity actually managed, since standing up
+/// can be blocked by a low ceiling.
+///
+/// Knockback goes through `apply_knockback` rather than writing `Velocity` directly: the impulse
+/// is applied by `apply_kinematics` (so it still collides with tiles) and the hit-stun that
+/// follows makes the movement systems ignore `axis` and jump/crouch intent until it wears off.
 #[derive(Component)]
 pub struct MovementState {
     pub on_ground: bool,
     pub wants_jump: bool,
+    pub wants_crouch: bool,
     pub axis: f32,
+    pub wall_contact: f32,
+    pub stance: Stance,
+    impulse: Option<Vec2>,
+    hit_stun: f32,
+}
+
+impl MovementState {
+    /// Replaces the entity's velocity with `impulse` on the next kinematics step and ignores its
+    /// movement intent for `stun` seconds.
+    pub fn apply_knockback(&mut self, impulse: Vec2, stun: f32) {
+        self.impulse = Some(impulse);
+        self.hit_stun = self.hit_stun.max(stun);
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.hit_stun > 0.0
+    }
+}
+
+/// Body posture. Crouching swaps in the shorter collider from `StanceColliders` and slows
+/// movement to a crawl.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Stance {
+    #[default]
+    Standing,
+    Crouching,
 }
 
 impl Default for MovementState {
@@ -85,33 +146,76 @@ impl Default for MovementState {
         Self {
             on_ground: true,
             wants_jump: false,
+            wants_crouch: false,
             axis: 0.0,
+            wall_contact: 0.0,
+            stance: Stance::Standing,
+            impulse: None,
+            hit_stun: 0.0,
         }
     }
 }
 
 /// Axis-aligned bounding box for collision checks. Only half extents are stored because they make
-/// overlap tests inexpensive.
-#[derive(Component, Copy, Clone)]
+/// overlap tests inexpensive. `offset` places the box relative to the entity's translation, so the
+/// hitbox can be smaller than (and shifted within) the sprite.
+#[derive(Component, Copy, Clone, Debug, PartialEq)]
 pub struct Collider {
     pub half_extents: Vec2,
+    pub offset: Vec2,
 }
 
 impl Collider {
     pub fn from_size(size: Vec2) -> Self {
         Self {
             half_extents: size * 0.5,
+            offset: Vec2::ZERO,
         }
     }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// World-space centre of the box for an entity whose translation is `position`.
+    pub fn center(&self, position: Vec2) -> Vec2 {
+        position + self.offset
+    }
+}
+
+/// Box that falls under gravity, stacks on other pushables, can be stood on, and slides sideways
+/// when the player walks into it. Needs a `Collider` and `Velocity`, and must not also be a
+/// character (`MovementController`).
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Pushable {
+    /// Fastest the player can walk while pushing this body.
+    pub push_speed: f32,
+}
+
+/// Collider for each stance. Entities without this component never crouch.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct StanceColliders {
+    pub standing: Collider,
+    pub crouching: Collider,
 }
 
 /// Samples keyboard input and writes intent into the movement state. Using a separate system keeps
-/// input handling deterministic and easy to test.
+/// input handling deterministic and easy to test. Input is ignored while a camera script plays and
+/// during hit-stun.
 fn read_player_input(
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&PlayerController, &mut Velocity, &mut MovementState)>,
+    director: Res<CameraDirector>,
+    mut query: Query<(&MovementController, &mut Velocity, &mut MovementState), With<Player>>,
 ) {
     for (_controller, mut velocity, mut state) in &mut query {
+        if director.is_playing() || state.is_stunned() {
+            state.axis = 0.0;
+            state.wants_jump = false;
+            state.wants_crouch = false;
+            continue;
+        }
+
         let mut axis: f32 = 0.0;
         if keyboard.pressed(KeyCode::KeyA) || keyboard.pressed(KeyCode::ArrowLeft) {
             axis -= 1.0;
@@ -126,6 +230,9 @@ fn read_player_input(
             state.wants_jump = true;
         }
 
+        state.wants_crouch =
+            keyboard.pressed(KeyCode::KeyS) || keyboard.pressed(KeyCode::ArrowDown);
+
         // Zero-out tiny residual velocities when grounded for crisp stopping behaviour.
         if state.axis.abs() < f32::EPSILON && state.on_ground && velocity.x.abs() < 1.0 {
             velocity.x = 0.0;
@@ -133,29 +240,78 @@ fn read_player_input(
     }
 }
 
-/// Applies physics each frame: acceleration toward target velocity, gravity, collision sweeps, and
-/// jump execution. All calculations mutate `Transform`/`Velocity` in place; Bevy batches those
-/// writes and applies them after the system completes.
+/// Applies physics each frame: stance changes, acceleration toward target velocity, gravity,
+/// collision sweeps, and jump execution. All calculations mutate `Transform`/`Velocity` in place;
+/// Bevy batches those writes and applies them after the system completes.
+#[allow(clippy::type_complexity)]
 fn apply_kinematics(
     time: Res<Time>,
     settings: Res<MovementSettings>,
     collision_map: Res<CollisionMap>,
-    mut query: Query<(
-        &mut Transform,
-        &mut Velocity,
-        &mut MovementState,
-        &PlayerController,
-        &Collider,
-    )>,
+    mut shakes: EventWriter<CameraShake>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut MovementState,
+            &MovementController,
+            &mut Collider,
+            Option<&StanceColliders>,
+            Has<Player>,
+        ),
+        Without<Pushable>,
+    >,
+    mut pushables: Query<
+        (Entity, &mut Transform, &Collider, &Pushable),
+        Without<MovementController>,
+    >,
 ) {
     let dt = time.delta_seconds();
+    let (entities, mut boxes): (Vec<Entity>, Vec<Body>) = pushables
+        .iter()
+        .map(|(entity, transform, collider, pushable)| {
+            (entity, Body::new(transform, collider, pushable))
+        })
+        .unzip();
+    // Characters as they stood at the start of the frame; they block pushed bodies.
+    let characters: Vec<(Entity, Body)> = query
+        .iter()
+        .map(|(entity, transform, _, _, _, collider, ..)| {
+            (entity, Body::character(transform, collider))
+        })
+        .collect();
+
+    for (
+        entity,
+        mut transform,
+        mut velocity,
+        mut state,
+        controller,
+        mut collider,
+        stances,
+        is_player,
+    ) in &mut query
+    {
+        if let Some(stances) = stances {
+            let translation = transform.translation;
+            update_stance(&mut state, &mut collider, stances, translation, &collision_map);
+        }
 
-    for (mut transform, mut velocity, mut state, controller, collider) in &mut query {
         // Capture jump intent so we can resolve collisions before applying it. This avoids the
         // classic "press jump on the landing frame" issue where intent would be cleared too early.
         let wants_jump = state.wants_jump;
         state.wants_jump = false;
 
+        if let Some(impulse) = state.impulse.take() {
+            velocity.0 = impulse;
+            if impulse.y > 0.0 {
+                state.on_ground = false;
+            }
+        }
+        let stunned = state.is_stunned();
+        state.hit_stun = (state.hit_stun - dt).max(0.0);
+
         if !state.on_ground {
             velocity.y -= settings.gravity * dt;
             if velocity.y < settings.terminal_velocity {
@@ -165,36 +321,136 @@ fn apply_kinematics(
             velocity.y = 0.0;
         }
 
-        let (accel_rate, max_speed) = if state.on_ground {
+        let (accel_rate, max_speed) = if state.on_ground && state.stance == Stance::Crouching {
+            (controller.ground_accel, controller.crouch_max_speed)
+        } else if state.on_ground {
             (controller.ground_accel, controller.ground_max_speed)
         } else {
             (controller.air_accel, controller.air_max_speed)
         };
 
-        if state.axis.abs() > f32::EPSILON {
+        if stunned {
+            // Knocked back: keep the launch momentum in the air, with some friction on the ground.
+            if state.on_ground {
+                velocity.x = move_towards(velocity.x, 0.0, accel_rate * 0.5 * dt);
+            }
+        } else if state.axis.abs() > f32::EPSILON {
             let target = state.axis * max_speed;
             velocity.x = move_towards(velocity.x, target, accel_rate * dt);
         } else {
             velocity.x = move_towards(velocity.x, 0.0, accel_rate * dt);
         }
 
-        let mut position = transform.translation;
+        // Collision is resolved on the collider's centre and mapped back to the entity afterwards.
+        let offset = collider.offset.extend(0.0);
+        let mut position = transform.translation + offset;
         let half = collider.half_extents;
+        let fall_speed = -velocity.y;
+
+        let start = position;
+        state.wall_contact =
+            resolve_horizontal(&mut position, &mut velocity.x, half, dt, &collision_map);
+        // Only the player pushes, and only with their feet on something.
+        let pushing = is_player && state.on_ground && !stunned;
+        let obstacles: Vec<Body> = if pushing {
+            characters
+                .iter()
+                .filter(|(other, _)| *other != entity)
+                .map(|(_, body)| *body)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if let Some(side) = resolve_bodies_horizontal(
+            start,
+            &mut position,
+            &mut velocity.x,
+            half,
+            pushing.then_some((dt, obstacles.as_slice())),
+            &mut boxes,
+            &collision_map,
+        ) {
+            state.wall_contact = side;
+        }
 
-        resolve_horizontal(&mut position, &mut velocity.x, half, dt, &collision_map);
-        let vertical_collision =
+        let start = position;
+        let mut vertical_collision =
             resolve_vertical(&mut position, &mut velocity.y, half, dt, &collision_map);
+        let body_collision =
+            resolve_bodies_vertical(start, &mut position, &mut velocity.y, half, &boxes);
+        if let Some(dir) = body_collision {
+            vertical_collision.down |= dir < 0.0;
+            vertical_collision.up |= dir > 0.0;
+        }
 
-        let grounded = vertical_collision.down || grounded_check(position, half, &collision_map);
+        let grounded = vertical_collision.down
+            || grounded_check(position, half, &collision_map)
+            || standing_on_body(position, half, &boxes);
+
+        if is_player && grounded && !state.on_ground && fall_speed > settings.hard_landing_speed {
+            let range = -settings.terminal_velocity - settings.hard_landing_speed;
+            let impact = ((fall_speed - settings.hard_landing_speed) / range).clamp(0.0, 1.0);
+            shakes.send(CameraShake {
+                intensity: 0.3 + 0.4 * impact,
+                duration: 0.35,
+            });
+        }
 
         state.on_ground = grounded;
 
-        if wants_jump && state.on_ground {
+        // Crouched players have to stand up (and have room to) before they can jump.
+        if wants_jump && !stunned && state.on_ground && state.stance == Stance::Standing {
             velocity.y = controller.jump_strength;
             state.on_ground = false;
         }
 
-        transform.translation = position;
+        transform.translation = position - offset;
+    }
+
+    for (entity, body) in entities.into_iter().zip(&boxes) {
+        if let Ok((_, mut transform, _, _)) = pushables.get_mut(entity) {
+            transform.translation.x = body.center.x - body.offset.x;
+        }
+    }
+}
+
+/// Enters a crouch when requested on the ground, and stands back up once the request is released
+/// and `ceiling_check` finds room overhead. Both colliders share a bottom edge, so swapping them
+/// never pushes the entity into the floor.
+fn update_stance(
+    state: &mut MovementState,
+    collider: &mut Collider,
+    stances: &StanceColliders,
+    translation: Vec3,
+    map: &CollisionMap,
+) {
+    match state.stance {
+        Stance::Standing if state.wants_crouch && state.on_ground => {
+            *collider = stances.crouching;
+            state.stance = Stance::Crouching;
+        }
+        Stance::Crouching if !state.wants_crouch => {
+            let crouching = stances.crouching;
+            let standing = stances.standing;
+            let center = translation + crouching.offset.extend(0.0);
+            let half = Vec2::new(standing.half_extents.x, crouching.half_extents.y);
+            let clearance = (standing.offset.y + standing.half_extents.y)
+                - (crouching.offset.y + crouching.half_extents.y);
+
+            if !ceiling_check(center, half, clearance, map) {
+                *collider = standing;
+                state.stance = Stance::Standing;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keeps the hurtbox of entities that change stance matching their current collider, so crouching
+/// ducks under arrows and swings aimed at standing height.
+fn sync_stance_hurtboxes(mut query: Query<(&Collider, &mut Hurtbox), With<StanceColliders>>) {
+    for (collider, mut hurtbox) in &mut query {
+        hurtbox.set_if_neq(Hurtbox::from_collider(collider));
     }
 }
 
@@ -208,53 +464,32 @@ const SKIN: f32 = 0.001;
 
 /// Resolves horizontal position/velocity against the collision map using a swept AABB. The tiny
 /// `SKIN` offset prevents the collider from getting stuck on edges by keeping it a hair away from
-/// solid tiles.
+/// solid tiles. Returns the direction of the wall that stopped the move, or 0.0 if nothing did.
 fn resolve_horizontal(
     position: &mut Vec3,
     velocity: &mut f32,
     half: Vec2,
     dt: f32,
     map: &CollisionMap,
-) {
+) -> f32 {
     if velocity.abs() < f32::EPSILON {
-        return;
+        return 0.0;
     }
 
-    let new_x = position.x + *velocity * dt;
+    let motion = Vec2::new(*velocity * dt, 0.0);
     let dir = velocity.signum();
 
-    let bottom = position.y - half.y + SKIN;
-    let top = position.y + half.y - SKIN;
-
-    let tile_size = map.tile_size.x;
-    let min_tile_y = ((bottom - map.origin.y) / map.tile_size.y).floor() as i32;
-    let max_tile_y = ((top - map.origin.y) / map.tile_size.y).floor() as i32;
-
-    if dir > 0.0 {
-        let edge = new_x + half.x;
-        let tile_x = ((edge - map.origin.x) / tile_size).floor() as i32;
-        for ty in min_tile_y..=max_tile_y {
-            if map.is_solid(IVec2::new(tile_x, ty)) {
-                let tile_left = map.origin.x + tile_x as f32 * tile_size;
-                position.x = tile_left - half.x - SKIN;
-                *velocity = 0.0;
-                return;
-            }
+    match map.sweep_aabb(position.truncate(), half, motion) {
+        Some(hit) => {
+            position.x = hit.position.x - dir * SKIN;
+            *velocity = 0.0;
+            dir
         }
-    } else if dir < 0.0 {
-        let edge = new_x - half.x;
-        let tile_x = ((edge - map.origin.x) / tile_size).floor() as i32;
-        for ty in min_tile_y..=max_tile_y {
-            if map.is_solid(IVec2::new(tile_x, ty)) {
-                let tile_right = map.origin.x + (tile_x + 1) as f32 * tile_size;
-                position.x = tile_right + half.x + SKIN;
-                *velocity = 0.0;
-                return;
-            }
+        None => {
+            position.x += motion.x;
+            0.0
         }
     }
-
-    position.x = new_x;
 }
 
 /// Vertical counterpart to `resolve_horizontal`. Returns whether a collision occurred above or
@@ -272,45 +507,260 @@ fn resolve_vertical(
         up: false,
     };
 
-    let new_y = position.y + *velocity * dt;
+    let motion = Vec2::new(0.0, *velocity * dt);
     let dir = velocity.signum();
-    let left = position.x - half.x + SKIN;
-    let right = position.x + half.x - SKIN;
-    let tile_width = map.tile_size.x;
-    let tile_height = map.tile_size.y;
-    let min_tile_x = ((left - map.origin.x) / tile_width).floor() as i32;
-    let max_tile_x = ((right - map.origin.x) / tile_width).floor() as i32;
-
-    if dir < 0.0 {
-        let edge = new_y - half.y;
-        let tile_y = ((edge - map.origin.y) / tile_height).floor() as i32;
-        for tx in min_tile_x..=max_tile_x {
-            if map.is_solid(IVec2::new(tx, tile_y)) {
-                let tile_top = map.origin.y + (tile_y + 1) as f32 * tile_height;
-                position.y = tile_top + half.y + SKIN;
-                *velocity = 0.0;
-                collision.down = true;
-                return collision;
-            }
-        }
-    } else if dir > 0.0 {
-        let edge = new_y + half.y;
-        let tile_y = ((edge - map.origin.y) / tile_height).floor() as i32;
-        for tx in min_tile_x..=max_tile_x {
-            if map.is_solid(IVec2::new(tx, tile_y)) {
-                let tile_bottom = map.origin.y + tile_y as f32 * tile_height;
-                position.y = tile_bottom - half.y - SKIN;
-                *velocity = 0.0;
-                collision.up = true;
-                return collision;
-            }
+
+    match map.sweep_aabb(position.truncate(), half, motion) {
+        Some(hit) => {
+            position.y = hit.position.y - dir * SKIN;
+            *velocity = 0.0;
+            collision.down = dir < 0.0;
+            collision.up = dir > 0.0;
         }
+        None => position.y += motion.y,
     }
 
-    position.y = new_y;
     collision
 }
 
+/// How many bodies one push can shove or carry in a chain.
+const MAX_PUSH_CHAIN: u32 = 8;
+
+/// A pushable's collider in world space, copied out of the ECS so bodies can be moved against each
+/// other without juggling overlapping queries.
+#[derive(Clone, Copy, Debug)]
+struct Body {
+    center: Vec2,
+    half: Vec2,
+    offset: Vec2,
+    push_speed: f32,
+}
+
+impl Body {
+    fn new(transform: &Transform, collider: &Collider, pushable: &Pushable) -> Self {
+        Self {
+            center: collider.center(transform.translation.truncate()),
+            half: collider.half_extents,
+            offset: collider.offset,
+            push_speed: pushable.push_speed,
+        }
+    }
+
+    /// A character's collider, as an obstacle for falling bodies. Characters are never pushed.
+    fn character(transform: &Transform, collider: &Collider) -> Self {
+        Self {
+            center: collider.center(transform.translation.truncate()),
+            half: collider.half_extents,
+            offset: collider.offset,
+            push_speed: 0.0,
+        }
+    }
+
+    /// Whether this body and the box at `center` overlap across `axis` (0 = x, 1 = y), i.e.
+    /// whether they would meet when one moves along it.
+    fn overlaps_across(&self, center: Vec2, half: Vec2, axis: usize) -> bool {
+        let across = 1 - axis;
+        (self.center[across] - center[across]).abs() < self.half[across] + half[across] - SKIN
+    }
+}
+
+/// Bodies a box at `center` would run into moving `motion` along `axis`, with the distance it can
+/// travel before touching each, nearest first. Bodies it already overlaps are ignored so nothing
+/// gets stuck inside one.
+fn bodies_ahead(
+    center: Vec2,
+    half: Vec2,
+    motion: f32,
+    axis: usize,
+    bodies: &[Body],
+    skip: Option<usize>,
+) -> Vec<(usize, f32)> {
+    let dir = motion.signum();
+    let mut ahead: Vec<(usize, f32)> = bodies
+        .iter()
+        .enumerate()
+        .filter(|(index, body)| Some(*index) != skip && body.overlaps_across(center, half, axis))
+        .filter_map(|(index, body)| {
+            let gap = (body.center[axis] - center[axis]) * dir - (body.half[axis] + half[axis]);
+            (gap > -SKIN && gap < motion.abs()).then_some((index, gap.max(0.0)))
+        })
+        .collect();
+    ahead.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ahead
+}
+
+/// Moves body `index` up to `distance` along x, shoving bodies in its way and carrying the ones
+/// stacked on top. Returns how far it actually moved; walls and `obstacles` (characters, which are
+/// never pushed) stop the whole chain.
+fn push_body(
+    bodies: &mut [Body],
+    obstacles: &[Body],
+    index: usize,
+    distance: f32,
+    map: &CollisionMap,
+    depth: u32,
+) -> f32 {
+    if depth > MAX_PUSH_CHAIN || distance.abs() < f32::EPSILON {
+        return 0.0;
+    }
+    let body = bodies[index];
+    let dir = distance.signum();
+
+    let mut allowed = match map.sweep_aabb(body.center, body.half, Vec2::new(distance, 0.0)) {
+        Some(hit) => hit.position.x - dir * SKIN - body.center.x,
+        None => distance,
+    };
+    let blocked = bodies_ahead(body.center, body.half, allowed, 0, obstacles, None);
+    if let Some(&(_, gap)) = blocked.first() {
+        allowed = dir * (gap - SKIN).max(0.0);
+    }
+    // Already touching a wall: the sweep backs off by `SKIN`, which must not pull the chain back.
+    if allowed * dir <= 0.0 {
+        return 0.0;
+    }
+    for (other, gap) in bodies_ahead(body.center, body.half, allowed, 0, bodies, Some(index)) {
+        let reach = dir * (gap - SKIN).max(0.0);
+        if reach.abs() >= allowed.abs() {
+            break;
+        }
+        allowed = reach + push_body(bodies, obstacles, other, allowed - reach, map, depth + 1);
+    }
+    if allowed * dir <= 0.0 {
+        return 0.0;
+    }
+
+    let top = body.center.y + body.half.y;
+    bodies[index].center.x += allowed;
+    for rider in 0..bodies.len() {
+        let resting = (bodies[rider].center.y - bodies[rider].half.y - top).abs() < SKIN * 4.0;
+        if rider != index && resting && bodies[rider].overlaps_across(body.center, body.half, 1) {
+            push_body(bodies, obstacles, rider, allowed, map, depth + 1);
+        }
+    }
+
+    allowed
+}
+
+/// Stops a character's horizontal move (from `start` to `position`) at the first body in the way,
+/// or pushes that body along when `push` (the frame time and the other characters) is given,
+/// slowing the character to the body's push speed. Returns the side of a body that blocked the
+/// move.
+fn resolve_bodies_horizontal(
+    start: Vec3,
+    position: &mut Vec3,
+    velocity: &mut f32,
+    half: Vec2,
+    push: Option<(f32, &[Body])>,
+    bodies: &mut [Body],
+    map: &CollisionMap,
+) -> Option<f32> {
+    let moved = position.x - start.x;
+    let (index, gap) = *bodies_ahead(start.truncate(), half, moved, 0, bodies, None).first()?;
+    let dir = moved.signum();
+    let reach = dir * (gap - SKIN).max(0.0);
+
+    if let Some((dt, obstacles)) = push {
+        let push_speed = bodies[index].push_speed;
+        let wanted = (moved - reach).clamp(-push_speed * dt, push_speed * dt);
+        let pushed = push_body(bodies, obstacles, index, wanted, map, 0);
+        position.x = start.x + reach + pushed;
+        *velocity = velocity.clamp(-push_speed, push_speed);
+        if pushed.abs() > f32::EPSILON {
+            return None;
+        }
+    } else {
+        position.x = start.x + reach;
+    }
+
+    *velocity = 0.0;
+    Some(dir)
+}
+
+/// Stops a character's vertical move (from `start` to `position`) on top of or under the first
+/// body in the way. Returns the direction of the move that was stopped.
+fn resolve_bodies_vertical(
+    start: Vec3,
+    position: &mut Vec3,
+    velocity: &mut f32,
+    half: Vec2,
+    bodies: &[Body],
+) -> Option<f32> {
+    let moved = position.y - start.y;
+    let (_, gap) = *bodies_ahead(start.truncate(), half, moved, 1, bodies, None).first()?;
+    let dir = moved.signum();
+
+    position.y = start.y + dir * (gap - SKIN).max(0.0);
+    *velocity = 0.0;
+    Some(dir)
+}
+
+/// Body counterpart to `grounded_check`: whether a box's bottom rests on top of any body.
+fn standing_on_body(position: Vec3, half: Vec2, bodies: &[Body]) -> bool {
+    let foot = position.y - half.y;
+    bodies.iter().any(|body| {
+        let gap = foot - (body.center.y + body.half.y);
+        (-SKIN..=SKIN * 4.0).contains(&gap) && body.overlaps_across(position.truncate(), half, 1)
+    })
+}
+
+/// Drops pushables under gravity, resolving against tiles, the bodies below, and characters, so a
+/// crate lands on a character's head instead of falling through it. Bodies are settled bottom-up,
+/// so each lands on where the one beneath it ends up this frame and stacks come to rest in a
+/// single step.
+#[allow(clippy::type_complexity)]
+fn apply_pushable_physics(
+    time: Res<Time>,
+    settings: Res<MovementSettings>,
+    collision_map: Res<CollisionMap>,
+    mut query: Query<
+        (&mut Transform, &mut Velocity, &Collider, &Pushable),
+        Without<MovementController>,
+    >,
+    characters: Query<(&Transform, &Collider), (With<MovementController>, Without<Pushable>)>,
+) {
+    let dt = time.delta_seconds();
+    let bottom = |transform: &Transform, collider: &Collider| {
+        transform.translation.y + collider.offset.y - collider.half_extents.y
+    };
+    let mut entries: Vec<_> = query.iter_mut().collect();
+    entries.sort_by(|(a, _, a_collider, _), (b, _, b_collider, _)| {
+        bottom(a, a_collider).total_cmp(&bottom(b, b_collider))
+    });
+    let mut boxes: Vec<Body> = entries
+        .iter()
+        .map(|(transform, _, collider, pushable)| Body::new(transform, collider, pushable))
+        .collect();
+    let characters: Vec<Body> = characters
+        .iter()
+        .map(|(transform, collider)| Body::character(transform, collider))
+        .collect();
+
+    for (index, (transform, velocity, _, _)) in entries.iter_mut().enumerate() {
+        velocity.x = 0.0;
+        velocity.y = (velocity.y - settings.gravity * dt).max(settings.terminal_velocity);
+
+        let Body { center, half, .. } = boxes[index];
+        let mut motion = velocity.y * dt;
+        let dir = motion.signum();
+        if let Some(hit) = collision_map.sweep_aabb(center, half, Vec2::new(0.0, motion)) {
+            motion = hit.position.y - dir * SKIN - center.y;
+            velocity.y = 0.0;
+        }
+        let nearest = bodies_ahead(center, half, motion, 1, &boxes, Some(index))
+            .into_iter()
+            .chain(bodies_ahead(center, half, motion, 1, &characters, None))
+            .map(|(_, gap)| gap)
+            .min_by(f32::total_cmp);
+        if let Some(gap) = nearest {
+            motion = dir * (gap - SKIN).max(0.0);
+            velocity.y = 0.0;
+        }
+
+        boxes[index].center.y += motion;
+        transform.translation.y += motion;
+    }
+}
+
 /// Moves `current` toward `target` by at most `max_delta`, preserving smooth acceleration and
 /// deceleration curves.
 fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
@@ -328,23 +778,151 @@ fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
 fn grounded_check(position: Vec3, half: Vec2, map: &CollisionMap) -> bool {
     let foot = position.y - half.y;
     let probe = foot - SKIN * 2.0;
-    let tile_height = map.tile_size.y;
-    let tile_width = map.tile_size.x;
-
-    let tile_y = ((probe - map.origin.y) / tile_height).floor() as i32;
-    let left = position.x - half.x + SKIN;
-    let right = position.x + half.x - SKIN;
-    let min_tile_x = ((left - map.origin.x) / tile_width).floor() as i32;
-    let max_tile_x = ((right - map.origin.x) / tile_width).floor() as i32;
-
-    for tx in min_tile_x..=max_tile_x {
-        if map.is_solid(IVec2::new(tx, tile_y)) {
-            let tile_top = map.origin.y + (tile_y + 1) as f32 * tile_height;
-            if foot >= tile_top - SKIN * 4.0 {
-                return true;
-            }
+    let left = map.world_to_tile(Vec2::new(position.x - half.x + SKIN, probe));
+    let right = map.world_to_tile(Vec2::new(position.x + half.x - SKIN, probe));
+
+    (left.x..=right.x)
+        .map(|tx| IVec2::new(tx, left.y))
+        .filter(|tile| map.is_solid(*tile))
+        .any(|tile| foot >= map.tile_to_world_rect(tile).max.y - SKIN * 4.0)
+}
+
+/// Ceiling counterpart to `grounded_check`. Checks the band from the top of the box up to
+/// `clearance` units above it and reports whether any solid tile is in the way, e.g. when a
+/// crouching player tries to stand up inside a one-tile tunnel.
+fn ceiling_check(position: Vec3, half: Vec2, clearance: f32, map: &CollisionMap) -> bool {
+    let head = position.y + half.y;
+    let band = Vec2::new(position.x, head + clearance * 0.5);
+    map.overlaps_solid(band, Vec2::new(half.x, clearance * 0.5))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: f32 = 16.0;
+
+    /// A 16-px crate resting on the floor (the top of tile row -1) at `x`.
+    fn crate_at(x: f32) -> Body {
+        Body {
+            center: Vec2::new(x, TILE * 0.5),
+            half: Vec2::splat(TILE * 0.5),
+            offset: Vec2::ZERO,
+            push_speed: 60.0,
+        }
+    }
+
+    /// A floor along tile row -1, plus any `walls`.
+    fn map_with(walls: &[(i32, i32)]) -> CollisionMap {
+        let mut map = CollisionMap {
+            tile_size: Vec2::splat(TILE),
+            ..default()
+        };
+        for x in -4..=8 {
+            map.add_solid(IVec2::new(x, -1));
+        }
+        for &(x, y) in walls {
+            map.add_solid(IVec2::new(x, y));
         }
+        map
+    }
+
+    #[test]
+    fn bodies_ahead_lists_the_ones_in_reach_nearest_first() {
+        let bodies = [
+            Body {
+                center: Vec2::new(30.0, 0.0),
+                ..crate_at(0.0)
+            },
+            Body {
+                center: Vec2::new(20.0, 0.0),
+                ..crate_at(0.0)
+            },
+            // Already overlapping, too far, and off to the side.
+            Body {
+                center: Vec2::new(5.0, 0.0),
+                ..crate_at(0.0)
+            },
+            Body {
+                center: Vec2::new(60.0, 0.0),
+                ..crate_at(0.0)
+            },
+            Body {
+                center: Vec2::new(20.0, 40.0),
+                ..crate_at(0.0)
+            },
+        ];
+
+        let ahead = bodies_ahead(Vec2::ZERO, Vec2::splat(8.0), 30.0, 0, &bodies, None);
+        assert_eq!(ahead, vec![(1, 4.0), (0, 14.0)]);
+        assert!(bodies_ahead(Vec2::ZERO, Vec2::splat(8.0), -30.0, 0, &bodies, None).is_empty());
+        assert_eq!(
+            bodies_ahead(Vec2::ZERO, Vec2::splat(8.0), 30.0, 0, &bodies, Some(1)),
+            vec![(0, 14.0)]
+        );
+    }
+
+    #[test]
+    fn pushing_shoves_the_whole_chain() {
+        let map = map_with(&[]);
+        let mut bodies = [crate_at(8.0), crate_at(24.0), crate_at(40.0)];
+
+        assert_eq!(push_body(&mut bodies, &[], 0, 4.0, &map, 0), 4.0);
+        let xs: Vec<f32> = bodies.iter().map(|body| body.center.x).collect();
+        assert_eq!(xs, [12.0, 28.0, 44.0]);
+    }
+
+    #[test]
+    fn a_wall_stops_the_whole_chain() {
+        // The second crate already touches a wall in column 3.
+        let map = map_with(&[(3, 0)]);
+        let mut bodies = [crate_at(24.0), crate_at(40.0)];
+
+        assert_eq!(push_body(&mut bodies, &[], 0, 4.0, &map, 0), 0.0);
+        assert_eq!(bodies[0].center.x, 24.0);
+        assert_eq!(bodies[1].center.x, 40.0);
+
+        // With a gap, the chain moves up to the wall and no further.
+        let mut bodies = [crate_at(8.0), crate_at(24.0)];
+        let moved = push_body(&mut bodies, &[], 0, 30.0, &map, 0);
+        assert!((moved - 16.0).abs() < 0.01, "moved {moved}");
+        assert!(bodies[1].center.x + 8.0 <= 48.0);
+    }
+
+    #[test]
+    fn riders_are_carried_along() {
+        let map = map_with(&[]);
+        let rider = Body {
+            center: Vec2::new(8.0, TILE * 1.5),
+            ..crate_at(0.0)
+        };
+        let mut bodies = [crate_at(8.0), rider, crate_at(-40.0)];
+
+        assert_eq!(push_body(&mut bodies, &[], 0, -4.0, &map, 0), -4.0);
+        assert_eq!(bodies[0].center.x, 4.0);
+        assert_eq!(bodies[1].center.x, 4.0);
+        // Bodies that aren't stacked on top stay where they are.
+        assert_eq!(bodies[2].center.x, -40.0);
     }
 
-    false
+    #[test]
+    fn characters_stop_the_chain() {
+        let map = map_with(&[]);
+        // An enemy standing 4 px past the second crate.
+        let enemy = Body {
+            center: Vec2::new(44.0, TILE * 0.5),
+            push_speed: 0.0,
+            ..crate_at(0.0)
+        };
+        let mut bodies = [crate_at(8.0), crate_at(24.0)];
+
+        let moved = push_body(&mut bodies, &[enemy], 0, 10.0, &map, 0);
+        assert!((moved - 4.0).abs() < 0.01, "moved {moved}");
+        assert!(bodies[1].center.x + 8.0 <= enemy.center.x - 8.0);
+
+        // Once touching, the enemy isn't shoved aside or walked through.
+        let moved = push_body(&mut bodies, &[enemy], 0, 4.0, &map, 0);
+        assert!(moved.abs() < 0.01, "moved {moved}");
+        assert!(bodies[1].center.x + 8.0 <= enemy.center.x - 8.0);
+    }
 }
@@ -0,0 +1,618 @@
+//! Platformer-aware navigation over the collision tile grid. Every empty tile standing on a solid
+//! one (with enough headroom above it) is a node. Nodes are linked by walking to a neighbour,
+//! falling off a ledge, or jumping up or across a gap. The graph holds every jump up to the bounds
+//! in `NavSettings`; each agent's search only follows the jumps its own `JumpReach` allows, derived
+//! from its controller's jump strength and speed and the gravity the movement systems use.
+//!
+//! The graph is rebuilt whenever the collision map is rebuilt. Runtime changes (doors, gates,
+//! breaking tiles) only re-link the nodes near the tiles listed in `CollisionMapChanged`. A* over
+//! the graph lets ground enemies reach the player across platforms instead of pacing below them.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::collision::{
+    collision_debug_enabled, CollisionMap, CollisionMapChanged, CollisionMapRebuilt,
+    CollisionSystems,
+};
+use crate::movement::MovementController;
+use crate::state::GameState;
+
+/// Registers the navigation graph and rebuilds it after the collision map.
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavSettings>()
+            .init_resource::<NavGraph>()
+            .add_systems(OnEnter(GameState::Loading), clear_nav_graph)
+            .add_systems(PostUpdate, rebuild_nav_graph.after(CollisionSystems))
+            .add_systems(Update, draw_nav_debug.run_if(collision_debug_enabled));
+    }
+}
+
+/// Limits used to compute the graph. Clearance is shared: every agent is assumed to fit in
+/// `clearance_tiles`. Jump links are generated up to the `max_jump_*` bounds, which should cover
+/// the most agile agent; how far a particular agent can actually jump is checked per search.
+#[derive(Resource, Clone, Debug)]
+pub struct NavSettings {
+    /// Empty tiles the agent needs above the tile it stands in (including that tile).
+    pub clearance_tiles: i32,
+    /// Deepest drop, in tiles, that counts as a fall link.
+    pub max_fall_tiles: i32,
+    /// Highest climb, in tiles, considered for jump links.
+    pub max_jump_rise: i32,
+    /// Widest horizontal distance, in tiles, considered for jump links.
+    pub max_jump_span: i32,
+}
+
+impl Default for NavSettings {
+    fn default() -> Self {
+        Self {
+            clearance_tiles: 2,
+            max_fall_tiles: 12,
+            max_jump_rise: 6,
+            max_jump_span: 10,
+        }
+    }
+}
+
+/// How an agent gets from one node to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavLinkKind {
+    Walk,
+    Fall,
+    Jump,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NavLink {
+    pub to: IVec2,
+    /// `to` relative to the node the link leaves from, in tiles.
+    pub offset: IVec2,
+    pub kind: NavLinkKind,
+    pub cost: f32,
+}
+
+/// One step of a path: the node to reach and how to get there from the previous one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NavStep {
+    pub tile: IVec2,
+    pub kind: NavLinkKind,
+}
+
+/// Standing nodes (tile coordinates) and their outgoing links.
+#[derive(Resource, Default)]
+pub struct NavGraph {
+    links: HashMap<IVec2, Vec<NavLink>>,
+    /// Highest solid row when the graph was last rebuilt.
+    top: i32,
+}
+
+impl NavGraph {
+    pub fn is_node(&self, tile: IVec2) -> bool {
+        self.links.contains_key(&tile)
+    }
+
+    pub fn links(&self, tile: IVec2) -> &[NavLink] {
+        self.links.get(&tile).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Node an agent whose feet are at `feet` stands on, looking a few tiles down for agents that
+    /// are mid-air.
+    pub fn node_below(&self, map: &CollisionMap, feet: Vec2, max_tiles: i32) -> Option<IVec2> {
+        let tile = map.world_to_tile(feet);
+        (0..=max_tiles)
+            .map(|dy| tile - IVec2::new(0, dy))
+            .find(|tile| self.is_node(*tile))
+    }
+
+    /// A* from `start` to `goal` for an agent that can jump as far as `reach`. The returned steps
+    /// exclude `start` and end with `goal`.
+    pub fn find_path(&self, start: IVec2, goal: IVec2, reach: &JumpReach) -> Option<Vec<NavStep>> {
+        if !self.is_node(start) || !self.is_node(goal) {
+            return None;
+        }
+
+        let heuristic = |tile: IVec2| {
+            let offset = (goal - tile).abs();
+            offset.x as f32 + offset.y as f32 * 0.5
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<IVec2, (IVec2, NavLinkKind)> = HashMap::new();
+        let mut best_cost: HashMap<IVec2, f32> = HashMap::from([(start, 0.0)]);
+        open.push(OpenNode {
+            tile: start,
+            estimate: heuristic(start),
+        });
+
+        while let Some(OpenNode { tile, .. }) = open.pop() {
+            if tile == goal {
+                let mut steps = Vec::new();
+                let mut current = goal;
+                while let Some(&(previous, kind)) = came_from.get(&current) {
+                    steps.push(NavStep {
+                        tile: current,
+                        kind,
+                    });
+                    current = previous;
+                }
+                steps.reverse();
+                return Some(steps);
+            }
+
+            let cost = best_cost[&tile];
+            for link in self.links(tile).iter().filter(|link| reach.allows(link)) {
+                let next_cost = cost + link.cost;
+                if best_cost
+                    .get(&link.to)
+                    .is_some_and(|&known| known <= next_cost)
+                {
+                    continue;
+                }
+
+                best_cost.insert(link.to, next_cost);
+                came_from.insert(link.to, (tile, link.kind));
+                open.push(OpenNode {
+                    tile: link.to,
+                    estimate: next_cost + heuristic(link.to),
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Rebuilds the graph from the solid tiles in `map`.
+    fn rebuild(&mut self, map: &CollisionMap, settings: &NavSettings) {
+        self.links.clear();
+        let Some(terrain) = Terrain::new(map, settings) else {
+            return;
+        };
+        self.top = terrain.top;
+
+        let nodes: HashSet<IVec2> = map
+            .solid_tiles()
+            .map(|tile| tile + IVec2::Y)
+            .filter(|tile| terrain.is_node(*tile))
+            .collect();
+        for node in nodes {
+            self.links.insert(node, terrain.links_from(node));
+        }
+    }
+
+    /// Re-links only the nodes whose links can depend on `tiles`, after those tiles changed
+    /// solidity. Falls back to a full rebuild if the top of the level moved.
+    fn update(&mut self, map: &CollisionMap, settings: &NavSettings, tiles: &[IVec2]) {
+        let Some(terrain) = Terrain::new(map, settings) else {
+            self.links.clear();
+            return;
+        };
+        if terrain.top != self.top {
+            self.rebuild(map, settings);
+            return;
+        }
+
+        // A node's links look at most `max_jump_span` tiles sideways, from below its deepest fall
+        // landing up to the headroom over its highest jump target.
+        let span = settings.max_jump_span.max(1);
+        let below = settings.max_jump_rise + terrain.clearance - 1;
+        let above = settings.max_fall_tiles + 1;
+        let affected: HashSet<IVec2> = tiles
+            .iter()
+            .flat_map(|tile| {
+                (tile.y - below..=tile.y + above).flat_map(move |y| {
+                    (tile.x - span..=tile.x + span).map(move |x| IVec2::new(x, y))
+                })
+            })
+            .collect();
+
+        for tile in affected {
+            if terrain.is_node(tile) {
+                self.links.insert(tile, terrain.links_from(tile));
+            } else {
+                self.links.remove(&tile);
+            }
+        }
+    }
+}
+
+/// Read-only view of the collision map used to work out nodes and links.
+struct Terrain<'a> {
+    map: &'a CollisionMap,
+    settings: &'a NavSettings,
+    /// Tiles above the highest solid are outside the level.
+    top: i32,
+    clearance: i32,
+}
+
+impl<'a> Terrain<'a> {
+    fn new(map: &'a CollisionMap, settings: &'a NavSettings) -> Option<Self> {
+        if map.is_empty() || map.tile_size.min_element() <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            map,
+            settings,
+            top: map.solid_tiles().map(|tile| tile.y).max()?,
+            clearance: settings.clearance_tiles.max(1),
+        })
+    }
+
+    fn is_clear(&self, tile: IVec2) -> bool {
+        (0..self.clearance).all(|dy| !self.map.is_solid(tile + IVec2::new(0, dy)))
+    }
+
+    fn is_node(&self, tile: IVec2) -> bool {
+        tile.y <= self.top && self.map.is_solid(tile - IVec2::Y) && self.is_clear(tile)
+    }
+
+    fn links_from(&self, node: IVec2) -> Vec<NavLink> {
+        let settings = self.settings;
+        let mut links = Vec::new();
+
+        for dir in [-1, 1] {
+            let side = node + IVec2::new(dir, 0);
+            if self.is_node(side) {
+                links.push(NavLink {
+                    to: side,
+                    offset: side - node,
+                    kind: NavLinkKind::Walk,
+                    cost: 1.0,
+                });
+            } else if self.is_clear(side) {
+                // Walk off the ledge and drop until something catches the agent.
+                let landing = (1..=settings.max_fall_tiles)
+                    .map(|dy| side - IVec2::new(0, dy))
+                    .take_while(|tile| !self.map.is_solid(*tile))
+                    .find(|tile| self.is_node(*tile));
+                if let Some(landing) = landing {
+                    links.push(NavLink {
+                        to: landing,
+                        offset: landing - node,
+                        kind: NavLinkKind::Fall,
+                        cost: 1.0 + (node.y - landing.y) as f32 * 0.5,
+                    });
+                }
+            }
+        }
+
+        let at_edge = |dir: i32| !self.is_node(node + IVec2::new(dir, 0));
+        for dy in 0..=settings.max_jump_rise {
+            for dx in 1..=settings.max_jump_span {
+                for dir in [-1, 1] {
+                    // Level jumps only make sense across a gap.
+                    if dy == 0 && (dx < 2 || !at_edge(dir)) {
+                        continue;
+                    }
+                    let target = node + IVec2::new(dir * dx, dy);
+                    if self.is_node(target)
+                        && jump_is_clear(node, target, &|tile| self.is_clear(tile))
+                    {
+                        links.push(NavLink {
+                            to: target,
+                            offset: target - node,
+                            kind: NavLinkKind::Jump,
+                            cost: 1.0 + dx as f32 + dy as f32,
+                        });
+                    }
+                }
+            }
+        }
+
+        links
+    }
+}
+
+/// Conservative arc check: the agent rises straight up to the target's height, then moves across
+/// at that height.
+fn jump_is_clear(from: IVec2, to: IVec2, is_clear: &impl Fn(IVec2) -> bool) -> bool {
+    let rising = (from.y..=to.y).all(|y| is_clear(IVec2::new(from.x, y)));
+    let (min_x, max_x) = (from.x.min(to.x), from.x.max(to.x));
+    rising && (min_x..=max_x).all(|x| is_clear(IVec2::new(x, to.y)))
+}
+
+/// How far (in tiles) a jump carries one agent for each height gained.
+pub struct JumpReach {
+    max_rise: i32,
+    speed: f32,
+    gravity: f32,
+    run_speed: f32,
+    tile_size: Vec2,
+}
+
+impl JumpReach {
+    pub fn new(jump_strength: f32, run_speed: f32, gravity: f32, tile_size: Vec2) -> Self {
+        let gravity = gravity.max(1.0);
+        let apex = jump_strength.powi(2) / (2.0 * gravity);
+        Self {
+            max_rise: (apex / tile_size.y).floor() as i32,
+            speed: jump_strength,
+            gravity,
+            run_speed,
+            tile_size,
+        }
+    }
+
+    /// Reach of a character moving with `controller` at its top ground speed.
+    pub fn for_controller(controller: &MovementController, gravity: f32, tile_size: Vec2) -> Self {
+        Self::new(
+            controller.jump_strength,
+            controller.ground_max_speed,
+            gravity,
+            tile_size,
+        )
+    }
+
+    /// Whether the agent can take `link`. Walking and falling are always possible.
+    pub fn allows(&self, link: &NavLink) -> bool {
+        link.kind != NavLinkKind::Jump
+            || (link.offset.y <= self.max_rise && link.offset.x.abs() <= self.reach(link.offset.y))
+    }
+
+    /// Horizontal tiles covered by a jump that lands `rise` tiles higher, i.e. the run speed times
+    /// the time until the jump comes back down to that height.
+    pub fn reach(&self, rise: i32) -> i32 {
+        let height = rise as f32 * self.tile_size.y;
+        let discriminant = self.speed * self.speed - 2.0 * self.gravity * height;
+        if discriminant < 0.0 {
+            return 0;
+        }
+        let airtime = (self.speed + discriminant.sqrt()) / self.gravity;
+        (airtime * self.run_speed / self.tile_size.x).floor() as i32
+    }
+}
+
+/// Open-set entry ordered so `BinaryHeap` pops the lowest estimate first.
+struct OpenNode {
+    tile: IVec2,
+    estimate: f32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+fn clear_nav_graph(mut graph: ResMut<NavGraph>) {
+    graph.links.clear();
+}
+
+fn rebuild_nav_graph(
+    mut rebuilt: EventReader<CollisionMapRebuilt>,
+    mut changed: EventReader<CollisionMapChanged>,
+    map: Res<CollisionMap>,
+    settings: Res<NavSettings>,
+    mut graph: ResMut<NavGraph>,
+) {
+    // Both readers must be drained every frame, so don't short-circuit.
+    let rebuilt = rebuilt.read().count() > 0;
+    let tiles: Vec<IVec2> = changed
+        .read()
+        .flat_map(|event| event.tiles.iter().copied())
+        .collect();
+
+    if rebuilt {
+        graph.rebuild(&map, &settings);
+        info!("Navigation graph rebuilt: {} nodes", graph.links.len());
+    } else if !tiles.is_empty() {
+        graph.update(&map, &settings, &tiles);
+    }
+}
+
+/// Draws nav links alongside the collision debug outlines: walks in blue, falls in orange, jumps
+/// in magenta.
+fn draw_nav_debug(graph: Res<NavGraph>, map: Res<CollisionMap>, mut gizmos: Gizmos) {
+    let center = |tile: IVec2| map.tile_to_world_rect(tile).center();
+
+    for (&node, links) in &graph.links {
+        for link in links {
+            let color = match link.kind {
+                NavLinkKind::Walk => Color::srgb(0.3, 0.5, 1.0),
+                NavLinkKind::Fall => Color::srgb(1.0, 0.6, 0.2),
+                NavLinkKind::Jump => Color::srgb(1.0, 0.3, 1.0),
+            };
+            gizmos.line_2d(center(node), center(link.to), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: Vec2 = Vec2::splat(16.0);
+    const GRAVITY: f32 = 1150.0;
+
+    /// A map of `tiles`, plus a solid far off to the side that raises the level's top above the
+    /// platforms under test.
+    fn map_over(tiles: impl IntoIterator<Item = IVec2>) -> CollisionMap {
+        let mut map = CollisionMap {
+            tile_size: TILE,
+            ..default()
+        };
+        for tile in tiles.into_iter().chain([IVec2::new(-40, 20)]) {
+            map.solids.insert(tile);
+            map.tile_values.insert(tile, 1);
+        }
+        map
+    }
+
+    fn graph_over(tiles: impl IntoIterator<Item = IVec2>) -> NavGraph {
+        let mut graph = NavGraph::default();
+        graph.rebuild(&map_over(tiles), &NavSettings::default());
+        graph
+    }
+
+    fn row(xs: std::ops::RangeInclusive<i32>, y: i32) -> impl Iterator<Item = IVec2> {
+        xs.map(move |x| IVec2::new(x, y))
+    }
+
+    fn link(graph: &NavGraph, from: IVec2, to: IVec2) -> Option<NavLinkKind> {
+        graph
+            .links(from)
+            .iter()
+            .find(|link| link.to == to)
+            .map(|link| link.kind)
+    }
+
+    /// An enemy with the default jump strength and chase speed.
+    fn enemy_reach() -> JumpReach {
+        JumpReach::new(480.0, 110.0, GRAVITY, TILE)
+    }
+
+    #[test]
+    fn links_walks_falls_and_jumps_back_up() {
+        // An upper floor with a drop of three tiles onto a lower one to its right.
+        let graph = graph_over(row(0..=3, 0).chain(row(4..=7, -3)));
+
+        assert_eq!(
+            link(&graph, IVec2::new(0, 1), IVec2::new(1, 1)),
+            Some(NavLinkKind::Walk)
+        );
+        assert_eq!(
+            link(&graph, IVec2::new(3, 1), IVec2::new(4, -2)),
+            Some(NavLinkKind::Fall)
+        );
+        assert_eq!(
+            link(&graph, IVec2::new(4, -2), IVec2::new(3, 1)),
+            Some(NavLinkKind::Jump)
+        );
+        // Nothing to land on past the lower floor's right edge.
+        assert!(graph
+            .links(IVec2::new(7, -2))
+            .iter()
+            .all(|link| link.offset.x <= 0));
+    }
+
+    #[test]
+    fn nodes_need_clearance() {
+        // A block one tile above the middle of the floor leaves too little headroom under it.
+        let graph = graph_over(row(0..=4, 0).chain([IVec2::new(2, 2)]));
+
+        assert!(graph.is_node(IVec2::new(1, 1)));
+        assert!(!graph.is_node(IVec2::new(2, 1)));
+        assert_eq!(link(&graph, IVec2::new(1, 1), IVec2::new(2, 1)), None);
+        // Jumping along the floor would pass under the block too, so the way across is over it.
+        assert_eq!(link(&graph, IVec2::new(1, 1), IVec2::new(3, 1)), None);
+        let path = graph
+            .find_path(IVec2::new(0, 1), IVec2::new(4, 1), &enemy_reach())
+            .unwrap();
+        assert!(path.iter().any(|step| step.tile == IVec2::new(2, 3)));
+        assert!(path.iter().all(|step| step.tile != IVec2::new(2, 1)));
+    }
+
+    #[test]
+    fn finds_a_path_across_a_gap() {
+        let graph = graph_over(row(0..=3, 0).chain(row(6..=9, 0)));
+        let (start, goal) = (IVec2::new(0, 1), IVec2::new(9, 1));
+
+        let path = graph.find_path(start, goal, &enemy_reach()).unwrap();
+        assert_eq!(path.last().map(|step| step.tile), Some(goal));
+        let jumps: Vec<_> = path
+            .iter()
+            .filter(|step| step.kind == NavLinkKind::Jump)
+            .collect();
+        assert_eq!(jumps.len(), 1);
+        assert!(jumps[0].tile.x >= 6);
+        // Every step follows a link of the graph.
+        let mut from = start;
+        for step in &path {
+            assert_eq!(link(&graph, from, step.tile), Some(step.kind));
+            from = step.tile;
+        }
+
+        // A slow agent can't clear the two-tile gap.
+        let slow = JumpReach::new(480.0, 30.0, GRAVITY, TILE);
+        assert!(graph.find_path(start, goal, &slow).is_none());
+    }
+
+    #[test]
+    fn jump_reach_follows_the_arc() {
+        let reach = enemy_reach();
+        // Apex at 480² / (2 · 1150) ≈ 100 px, so six tiles up at most.
+        assert_eq!(reach.max_rise, 6);
+        // 0.83 s of airtime at 110 px/s on the level.
+        assert_eq!(reach.reach(0), 5);
+        assert_eq!(reach.reach(6), 3);
+        assert_eq!(reach.reach(7), 0);
+        assert!((0..6).all(|rise| reach.reach(rise) >= reach.reach(rise + 1)));
+
+        let jump = |offset: IVec2| NavLink {
+            to: offset,
+            offset,
+            kind: NavLinkKind::Jump,
+            cost: 1.0,
+        };
+        assert!(reach.allows(&jump(IVec2::new(-5, 0))));
+        assert!(!reach.allows(&jump(IVec2::new(6, 0))));
+        assert!(!reach.allows(&jump(IVec2::new(1, 7))));
+        assert!(reach.allows(&NavLink {
+            kind: NavLinkKind::Fall,
+            ..jump(IVec2::new(9, -12))
+        }));
+
+        let player = JumpReach::for_controller(&MovementController::default(), GRAVITY, TILE);
+        assert!(player.reach(0) > reach.reach(0));
+    }
+
+    #[test]
+    fn local_updates_match_a_full_rebuild() {
+        let settings = NavSettings::default();
+        let mut map = map_over(row(0..=12, 0).chain(row(4..=8, 4)));
+        let mut graph = NavGraph::default();
+        graph.rebuild(&map, &settings);
+
+        // A door closes across the floor and a hole is broken into it.
+        let mut tiles: Vec<IVec2> = [IVec2::new(6, 1), IVec2::new(6, 2)]
+            .into_iter()
+            .filter(|tile| map.add_blocker(*tile))
+            .collect();
+        map.remove_solid(IVec2::new(2, 0));
+        tiles.push(IVec2::new(2, 0));
+        graph.update(&map, &settings, &tiles);
+
+        let mut fresh = NavGraph::default();
+        fresh.rebuild(&map, &settings);
+        assert_eq!(graph.links, fresh.links);
+        assert!(!graph.is_node(IVec2::new(2, 1)));
+        assert_eq!(link(&graph, IVec2::new(5, 1), IVec2::new(6, 1)), None);
+    }
+
+    #[test]
+    fn crumbled_tiles_drop_out_of_the_graph_and_come_back() {
+        let settings = NavSettings::default();
+        let mut map = map_over(row(0..=8, 0));
+        let mut graph = NavGraph::default();
+        graph.rebuild(&map, &settings);
+        let original = graph.links.clone();
+
+        let tile = IVec2::new(4, 0);
+        map.remove_solid(tile);
+        graph.update(&map, &settings, &[tile]);
+        assert!(!graph.is_node(IVec2::new(4, 1)));
+        assert_eq!(link(&graph, IVec2::new(3, 1), IVec2::new(4, 1)), None);
+
+        map.add_solid(tile);
+        graph.update(&map, &settings, &[tile]);
+        assert_eq!(graph.links, original);
+    }
+}
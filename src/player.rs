@@ -6,21 +6,27 @@
 
 use bevy::prelude::*;
 
-use crate::level::LevelAssets;
-use crate::movement::{Collider, MovementState, PlayerController, Velocity};
+use crate::collision::{CollisionMap, CollisionMapRebuilt, CollisionSystems};
+use crate::health::{Faction, Health, Hurtbox};
+use crate::level::{LevelAssets, DEFAULT_SPAWN_OFFSET};
+use crate::movement::{Collider, MovementController, MovementState, StanceColliders, Velocity};
 use crate::state::GameState;
-use crate::transition::SpawnPositions;
 
 /// Registers systems that keep exactly one player entity alive while in the `Playing` state.
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            spawn_player_if_needed.run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(OnExit(GameState::Playing), despawn_player);
+        app.init_resource::<PlayerCharacter>()
+            .add_systems(
+                Update,
+                spawn_player_if_needed.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_player)
+            .add_systems(
+                PostUpdate,
+                relocate_player_after_rebuild.after(CollisionSystems),
+            );
     }
 }
 
@@ -29,13 +35,64 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player;
 
+/// Visual and physical dimensions of a character. The collider is configured independently of the
+/// sprite so a character can be drawn larger than the box it collides with.
+#[derive(Clone, Debug)]
+pub struct CharacterDefinition {
+    pub sprite_size: Vec2,
+    pub collider_size: Vec2,
+    /// Collider centre relative to the sprite centre.
+    pub collider_offset: Vec2,
+    /// Crouching hitbox; keep its bottom edge level with the standing one.
+    pub crouch_collider_size: Vec2,
+    pub crouch_collider_offset: Vec2,
+}
+
+impl CharacterDefinition {
+    pub fn collider(&self) -> Collider {
+        Collider::from_size(self.collider_size).with_offset(self.collider_offset)
+    }
+
+    pub fn stance_colliders(&self) -> StanceColliders {
+        StanceColliders {
+            standing: self.collider(),
+            crouching: Collider::from_size(self.crouch_collider_size)
+                .with_offset(self.crouch_collider_offset),
+        }
+    }
+}
+
+/// Character definition used when spawning the player. The default hitbox is narrower than a tile
+/// and shorter than two, with its bottom edge level with the sprite's feet; crouched, it is short
+/// enough to crawl through one-tile tunnels.
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerCharacter(pub CharacterDefinition);
+
+impl Default for PlayerCharacter {
+    fn default() -> Self {
+        Self(CharacterDefinition {
+            sprite_size: Vec2::splat(32.0),
+            collider_size: Vec2::new(12.0, 28.0),
+            collider_offset: Vec2::new(0.0, -2.0),
+            crouch_collider_size: Vec2::new(12.0, 12.0),
+            crouch_collider_offset: Vec2::new(0.0, -10.0),
+        })
+    }
+}
+
+/// Hit points the player starts each level with.
+const PLAYER_MAX_HEALTH: i32 = 5;
+
+/// Seconds of invulnerability after the player is hit.
+const PLAYER_INVULNERABILITY: f32 = 1.0;
+
 /// Spawns the player once the LDtk world origin is known. If the world is still loading or a
 /// player already exists, the system exits earl
 fn spawn_player_if_needed(
     mut commands: Commands,
     level_assets: Res<LevelAssets>,
     asset_server: Res<AssetServer>,
-    spawn_positions: Res<SpawnPositions>,
+    character: Res<PlayerCharacter>,
     existing_player: Query<Entity, With<Player>>,
 ) {
     if !existing_player.is_empty() {
@@ -46,15 +103,14 @@ fn spawn_player_if_needed(
         return;
     };
 
-    // Get spawn position based on the current level file
-    let project_path = level_assets.project_path.as_deref().unwrap_or("levels/test_map_1_newres.ldtk");
-    let spawn_offset = spawn_positions.get(project_path);
+    // The spawn offset comes from the campaign entry chosen when the level was spawned.
+    let spawn_offset = level_assets.spawn_offset.unwrap_or(DEFAULT_SPAWN_OFFSET);
     let spawn_2d = origin + spawn_offset;
     // Place the sprite slightly in front of tile layers so it renders above the map.
     let spawn_position = spawn_2d.extend(200.0);
 
     let texture = asset_server.load("textures/blob.png");
-    let sprite_size = Vec2::splat(32.0);
+    let character = &character.0;
 
     // Spawn the player entity. The tuple inserted into the ECS is stored in a contiguous archetype
     // row, so memory access during gameplay remains cache-friendly.
@@ -64,7 +120,7 @@ fn spawn_player_if_needed(
         SpriteBundle {
             texture,
             sprite: Sprite {
-                custom_size: Some(sprite_size),
+                custom_size: Some(character.sprite_size),
                 ..default()
             },
             transform: Transform::from_translation(spawn_position),
@@ -72,8 +128,12 @@ fn spawn_player_if_needed(
         },
         Velocity::default(),
         MovementState::default(),
-        PlayerController::default(),
-        Collider::from_size(sprite_size),
+        MovementController::default(),
+        character.collider(),
+        character.stance_colliders(),
+        Hurtbox::from_collider(&character.collider()),
+        Faction::Player,
+        Health::new(PLAYER_MAX_HEALTH).with_invulnerability(PLAYER_INVULNERABILITY),
     ));
 }
 
@@ -84,3 +144,40 @@ fn despawn_player(mut commands: Commands, query: Query<Entity, With<Player>>) {
         commands.entity(entity).despawn_recursive();
     }
 }
+
+/// How far (in tiles) to search for free space when a rebuilt map buries the player.
+const RELOCATE_SEARCH_TILES: i32 = 8;
+
+/// Keeps the player where they were when the collision map is rebuilt underneath them (typically an
+/// LDtk hot reload). If the new geometry overlaps their collider, they are moved to the nearest
+/// free spot and their velocity is cleared so stale momentum doesn't carry into the new layout.
+fn relocate_player_after_rebuild(
+    mut events: EventReader<CollisionMapRebuilt>,
+    map: Res<CollisionMap>,
+    mut query: Query<
+        (&mut Transform, &Collider, &mut Velocity, &mut MovementState),
+        With<Player>,
+    >,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    for (mut transform, collider, mut velocity, mut state) in &mut query {
+        let center = collider.center(transform.translation.truncate());
+        if !map.overlaps_solid(center, collider.half_extents) {
+            continue;
+        }
+
+        match map.nearest_free_position(center, collider.half_extents, RELOCATE_SEARCH_TILES) {
+            Some(free) => {
+                info!("Player embedded after collision rebuild; moved to {:?}", free);
+                transform.translation.x = free.x - collider.offset.x;
+                transform.translation.y = free.y - collider.offset.y;
+                velocity.0 = Vec2::ZERO;
+                state.on_ground = false;
+            }
+            None => warn!("Player embedded after collision rebuild and no free spot was found nearby"),
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! Thrown and fired projectiles: the player's daggers, enemy arrows, and darts from LDtk
+//! `ArrowTrap` entities. Each projectile has a velocity, a gravity scale, a lifetime, and rules for
+//! what happens when it meets a solid tile (stop or bounce) or a hurtbox (stop, or pierce through a
+//! number of targets).
+//!
+//! Projectile entities are pooled. A finished projectile is hidden and parked in `ProjectilePool`
+//! rather than despawned, and the next `SpawnProjectile` reuses it, so traps firing every second
+//! don't churn entities and component storage.
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::breakable::StrikeTile;
+use crate::cinematic::CameraDirector;
+use crate::collision::{CollisionMap, BREAKABLE_TILE};
+use crate::health::{DamageEvent, Faction, Hurtbox};
+use crate::movement::{MovementSettings, MovementState};
+use crate::player::Player;
+use crate::state::{GameSet, GameState};
+
+/// Registers projectile events, the pool, the `ArrowTrap` LDtk entity, and the simulation systems.
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectilePool>()
+            .add_event::<SpawnProjectile>()
+            .add_event::<ProjectileHit>()
+            .register_ldtk_entity::<ArrowTrapBundle>("ArrowTrap")
+            .add_systems(OnEnter(GameState::Loading), recycle_all_projectiles)
+            .add_systems(
+                Update,
+                (
+                    (throw_dagger, fire_arrow_traps).in_set(GameSet::Input),
+                    (spawn_projectiles, move_projectiles, hit_hurtboxes)
+                        .chain()
+                        .in_set(GameSet::Movement),
+                    spawn_hit_sparks.in_set(GameSet::Effects),
+                    update_hit_sparks,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Distance a bounced projectile is pushed off the surface, so it doesn't start the next frame
+/// touching the tile it bounced off.
+const SKIN: f32 = 0.01;
+
+/// Seconds between player dagger throws.
+const DAGGER_COOLDOWN: f32 = 0.35;
+
+/// Sparks thrown out where a projectile hits something, and how long they last.
+const SPARK_COUNT: usize = 4;
+const SPARK_LIFETIME: f32 = 0.2;
+
+/// What a projectile does when it runs into a solid tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolidResponse {
+    Destroy,
+    /// Reflect off the surface, keeping `restitution` of the speed, up to `max_bounces` times.
+    Bounce { restitution: f32, max_bounces: u32 },
+}
+
+/// Everything needed to launch a projectile.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectileSpec {
+    pub velocity: Vec2,
+    /// Multiplier on `MovementSettings::gravity`; 0 flies straight.
+    pub gravity_scale: f32,
+    /// Seconds before the projectile disappears on its own.
+    pub lifetime: f32,
+    pub damage: i32,
+    /// Extra targets the projectile passes through before stopping.
+    pub pierce: u32,
+    pub on_solid: SolidResponse,
+    pub size: Vec2,
+    pub color: Color,
+    pub faction: Faction,
+}
+
+impl ProjectileSpec {
+    /// The player's thrown dagger: a short arc that bounces once.
+    pub fn dagger(direction: f32) -> Self {
+        Self {
+            velocity: Vec2::new(direction.signum() * 420.0, 120.0),
+            gravity_scale: 0.6,
+            lifetime: 1.5,
+            damage: 1,
+            pierce: 0,
+            on_solid: SolidResponse::Bounce {
+                restitution: 0.4,
+                max_bounces: 1,
+            },
+            size: Vec2::new(10.0, 4.0),
+            color: Color::srgb(0.85, 0.85, 0.95),
+            faction: Faction::Player,
+        }
+    }
+
+    /// A fast, slightly dropping arrow.
+    pub fn arrow(direction: Vec2, faction: Faction) -> Self {
+        Self {
+            velocity: direction.normalize_or_zero() * 360.0,
+            gravity_scale: 0.15,
+            lifetime: 3.0,
+            damage: 1,
+            pierce: 0,
+            on_solid: SolidResponse::Destroy,
+            size: Vec2::new(12.0, 3.0),
+            color: Color::srgb(0.7, 0.55, 0.35),
+            faction,
+        }
+    }
+
+    /// A trap dart: flies straight and hurts anyone it touches.
+    pub fn dart(direction: Vec2) -> Self {
+        Self {
+            velocity: direction.normalize_or_zero() * 300.0,
+            gravity_scale: 0.0,
+            lifetime: 4.0,
+            damage: 1,
+            pierce: 0,
+            on_solid: SolidResponse::Destroy,
+            size: Vec2::new(8.0, 2.0),
+            color: Color::srgb(0.55, 0.75, 0.4),
+            faction: Faction::Neutral,
+        }
+    }
+}
+
+/// Launches a projectile centred on `position`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SpawnProjectile {
+    pub position: Vec2,
+    pub spec: ProjectileSpec,
+}
+
+/// Sent when a projectile strikes a hurtbox, and drives the hit sparks. The matching `DamageEvent`
+/// is sent alongside.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ProjectileHit {
+    pub projectile: Entity,
+    pub target: Entity,
+    pub point: Vec2,
+}
+
+/// A live projectile. Pooled entities keep this component while parked, with `active` cleared.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    pub spec: ProjectileSpec,
+    pub velocity: Vec2,
+    active: bool,
+    age: f32,
+    pierces_left: u32,
+    bounces: u32,
+    /// Targets already struck, so a piercing projectile hits each one once.
+    struck: Vec<Entity>,
+}
+
+/// Parked projectile entities waiting to be reused.
+#[derive(Resource, Default)]
+pub struct ProjectilePool {
+    free: Vec<Entity>,
+}
+
+impl ProjectilePool {
+    fn recycle(
+        &mut self,
+        entity: Entity,
+        projectile: &mut Projectile,
+        visibility: &mut Visibility,
+    ) {
+        projectile.active = false;
+        projectile.struck.clear();
+        *visibility = Visibility::Hidden;
+        self.free.push(entity);
+    }
+}
+
+fn recycle_all_projectiles(
+    mut pool: ResMut<ProjectilePool>,
+    mut projectiles: Query<(Entity, &mut Projectile, &mut Visibility)>,
+) {
+    for (entity, mut projectile, mut visibility) in &mut projectiles {
+        if projectile.active {
+            pool.recycle(entity, &mut projectile, &mut visibility);
+        }
+    }
+}
+
+/// Throws a dagger the way the player is facing when `F` is pressed, unless a camera script is
+/// playing or the player is in hit-stun.
+fn throw_dagger(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    director: Res<CameraDirector>,
+    time: Res<Time>,
+    mut cooldown: Local<f32>,
+    player: Query<(&Transform, &Sprite, &MovementState), With<Player>>,
+    mut spawns: EventWriter<SpawnProjectile>,
+) {
+    *cooldown = (*cooldown - time.delta_seconds()).max(0.0);
+    if director.is_playing() || *cooldown > 0.0 || !keyboard.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+    let Ok((transform, sprite, state)) = player.get_single() else {
+        return;
+    };
+    if state.is_stunned() {
+        return;
+    }
+
+    let direction = if sprite.flip_x { -1.0 } else { 1.0 };
+    spawns.send(SpawnProjectile {
+        position: transform.translation.truncate() + Vec2::new(direction * 8.0, 4.0),
+        spec: ProjectileSpec::dagger(direction),
+    });
+    *cooldown = DAGGER_COOLDOWN;
+}
+
+/// Activates a pooled projectile for each request, spawning a new entity only when the pool is
+/// empty.
+fn spawn_projectiles(
+    mut commands: Commands,
+    mut requests: EventReader<SpawnProjectile>,
+    mut pool: ResMut<ProjectilePool>,
+    mut pooled: Query<(&mut Projectile, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    for request in requests.read() {
+        let projectile = Projectile {
+            spec: request.spec,
+            velocity: request.spec.velocity,
+            active: true,
+            age: 0.0,
+            pierces_left: request.spec.pierce,
+            bounces: 0,
+            struck: Vec::new(),
+        };
+        let transform = Transform::from_translation(request.position.extend(180.0))
+            .with_rotation(facing_rotation(request.spec.velocity));
+
+        let reused = pool
+            .free
+            .pop()
+            .and_then(|entity| pooled.get_mut(entity).ok());
+        match reused {
+            Some((mut slot, mut slot_transform, mut sprite, mut visibility)) => {
+                // Keep the old `struck` allocation for reuse.
+                let struck = std::mem::take(&mut slot.struck);
+                *slot = Projectile {
+                    struck,
+                    ..projectile
+                };
+                *slot_transform = transform;
+                sprite.color = request.spec.color;
+                sprite.custom_size = Some(request.spec.size);
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                commands.spawn((
+                    Name::new("Projectile"),
+                    projectile,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: request.spec.color,
+                            custom_size: Some(request.spec.size),
+                            ..default()
+                        },
+                        transform,
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn facing_rotation(velocity: Vec2) -> Quat {
+    Quat::from_rotation_z(velocity.y.atan2(velocity.x))
+}
+
+/// Applies gravity, ages projectiles out, and resolves solid tiles by stopping or bouncing. The
+/// player's projectiles also strike the tiles they hit, breaking breakable ones.
+fn move_projectiles(
+    time: Res<Time>,
+    settings: Res<MovementSettings>,
+    map: Res<CollisionMap>,
+    mut pool: ResMut<ProjectilePool>,
+    mut strikes: EventWriter<StrikeTile>,
+    mut projectiles: Query<(Entity, &mut Projectile, &mut Transform, &mut Visibility)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut projectile, mut transform, mut visibility) in &mut projectiles {
+        if !projectile.active {
+            continue;
+        }
+
+        projectile.age += dt;
+        if projectile.age >= projectile.spec.lifetime {
+            pool.recycle(entity, &mut projectile, &mut visibility);
+            continue;
+        }
+
+        projectile.velocity.y -= settings.gravity * projectile.spec.gravity_scale * dt;
+        projectile.velocity.y = projectile.velocity.y.max(settings.terminal_velocity);
+
+        let position = transform.translation.truncate();
+        let half = projectile.spec.size * 0.5;
+        let motion = projectile.velocity * dt;
+
+        let next = match map.sweep_aabb(position, half, motion) {
+            None => position + motion,
+            Some(hit)
+                if projectile.spec.faction == Faction::Player
+                    && map.get_tile_value(hit.tile) == Some(BREAKABLE_TILE) =>
+            {
+                strikes.send(StrikeTile { tile: hit.tile });
+                pool.recycle(entity, &mut projectile, &mut visibility);
+                continue;
+            }
+            Some(hit) => match projectile.spec.on_solid {
+                SolidResponse::Bounce {
+                    restitution,
+                    max_bounces,
+                } if projectile.bounces < max_bounces => {
+                    projectile.bounces += 1;
+                    let velocity = projectile.velocity;
+                    projectile.velocity =
+                        (velocity - 2.0 * velocity.dot(hit.normal) * hit.normal) * restitution;
+                    hit.position + hit.normal * SKIN
+                }
+                _ => {
+                    pool.recycle(entity, &mut projectile, &mut visibility);
+                    continue;
+                }
+            },
+        };
+
+        transform.translation.x = next.x;
+        transform.translation.y = next.y;
+        transform.rotation = facing_rotation(projectile.velocity);
+    }
+}
+
+/// Strikes hostile hurtboxes overlapping each projectile. Non-piercing projectiles stop at the
+/// first target; piercing ones carry on until they run out of pierces.
+fn hit_hurtboxes(
+    mut pool: ResMut<ProjectilePool>,
+    mut projectiles: Query<(Entity, &mut Projectile, &Transform, &mut Visibility)>,
+    targets: Query<(Entity, &Transform, &Hurtbox, &Faction), Without<Projectile>>,
+    mut hits: EventWriter<ProjectileHit>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (entity, mut projectile, transform, mut visibility) in &mut projectiles {
+        if !projectile.active {
+            continue;
+        }
+
+        let center = transform.translation.truncate();
+        let half = projectile.spec.size * 0.5;
+
+        for (target, target_transform, hurtbox, faction) in &targets {
+            if !projectile.spec.faction.is_hostile_to(*faction)
+                || projectile.struck.contains(&target)
+                || !hurtbox.overlaps(target_transform.translation.truncate(), center, half)
+            {
+                continue;
+            }
+
+            projectile.struck.push(target);
+            hits.send(ProjectileHit {
+                projectile: entity,
+                target,
+                point: center,
+            });
+            damage.send(DamageEvent {
+                target,
+                amount: projectile.spec.damage,
+                source: Some(center),
+                knockback: None,
+            });
+
+            if projectile.pierces_left == 0 {
+                pool.recycle(entity, &mut projectile, &mut visibility);
+                break;
+            }
+            projectile.pierces_left -= 1;
+        }
+    }
+}
+
+/// Short-lived spark thrown out where a projectile strikes a target.
+#[derive(Component)]
+struct HitSpark {
+    velocity: Vec2,
+    remaining: f32,
+}
+
+/// Throws a small fan of sparks, in the projectile's colour, away from whatever it hit.
+fn spawn_hit_sparks(
+    mut commands: Commands,
+    mut hits: EventReader<ProjectileHit>,
+    projectiles: Query<&Projectile>,
+    targets: Query<&GlobalTransform>,
+) {
+    for hit in hits.read() {
+        let color = projectiles
+            .get(hit.projectile)
+            .map_or(Color::WHITE, |projectile| projectile.spec.color);
+        let away = targets
+            .get(hit.target)
+            .ok()
+            .and_then(|target| (hit.point - target.translation().truncate()).try_normalize())
+            .unwrap_or(Vec2::Y);
+
+        for spark in 0..SPARK_COUNT {
+            // Spread over a quarter circle centred on the outward direction.
+            let t = spark as f32 / (SPARK_COUNT - 1) as f32;
+            let angle = (t - 0.5) * std::f32::consts::FRAC_PI_2;
+            commands.spawn((
+                Name::new("HitSpark"),
+                HitSpark {
+                    velocity: Vec2::from_angle(angle).rotate(away) * 140.0,
+                    remaining: SPARK_LIFETIME,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(2.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(hit.point.extend(190.0)),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn update_hit_sparks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sparks: Query<(Entity, &mut HitSpark, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut spark, mut transform) in &mut sparks {
+        spark.remaining -= dt;
+        if spark.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation += (spark.velocity * dt).extend(0.0);
+    }
+}
+
+/// LDtk-authored dart launcher.
+///
+/// Fields: `Direction` (Enum with values `Left`, `Right`, `Up`, `Down`; default `Right`) and
+/// `Interval` (Float, seconds between shots, default 1).
+#[derive(Component, Clone, Debug)]
+pub struct ArrowTrap {
+    direction: Vec2,
+    interval: f32,
+    timer: f32,
+}
+
+#[derive(Bundle)]
+struct ArrowTrapBundle {
+    trap: ArrowTrap,
+}
+
+impl LdtkEntity for ArrowTrapBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let direction = match entity_instance
+            .get_enum_field("Direction")
+            .map(String::as_str)
+        {
+            Ok("Left") => Vec2::NEG_X,
+            Ok("Up") => Vec2::Y,
+            Ok("Down") => Vec2::NEG_Y,
+            _ => Vec2::X,
+        };
+        let interval = entity_instance
+            .get_float_field("Interval")
+            .copied()
+            .unwrap_or(1.0)
+            .max(0.05);
+
+        Self {
+            trap: ArrowTrap {
+                direction,
+                interval,
+                timer: interval,
+            },
+        }
+    }
+}
+
+fn fire_arrow_traps(
+    time: Res<Time>,
+    mut traps: Query<(&mut ArrowTrap, &GlobalTransform)>,
+    mut spawns: EventWriter<SpawnProjectile>,
+) {
+    for (mut trap, transform) in &mut traps {
+        trap.timer -= time.delta_seconds();
+        if trap.timer > 0.0 {
+            continue;
+        }
+
+        trap.timer += trap.interval;
+        spawns.send(SpawnProjectile {
+            position: transform.translation().truncate(),
+            spec: ProjectileSpec::dart(trap.direction),
+        });
+    }
+}
@@ -0,0 +1,140 @@
+//! Crates placed as `Crate` entities in LDtk. They fall, stack, can be stood on, and slide when the
+//! player walks into them, all handled by the `Pushable` body support in the movement systems.
+//! Crates have colliders, so they also weigh down pressure plates.
+//!
+//! As with enemies, the LDtk entity only marks where a crate starts; the crate itself is a
+//! top-level entity in world space. Crates go back to their authored spots whenever the level is
+//! loaded again.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_ldtk::utils::ldtk_pixel_coords_to_translation_pivoted;
+
+use crate::level::LevelAssets;
+use crate::movement::{Collider, Pushable, Velocity};
+use crate::state::{GameSet, GameState};
+
+/// Registers the LDtk crate marker and spawns crates from it.
+pub struct PushablePlugin;
+
+impl Plugin for PushablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrateRoster>()
+            .register_ldtk_entity::<CrateSpawnBundle>("Crate")
+            .add_systems(OnEnter(GameState::Loading), despawn_crates)
+            .add_systems(
+                Update,
+                spawn_crates
+                    .before(GameSet::Input)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Marker for spawned crates.
+#[derive(Component)]
+pub struct Crate;
+
+/// LDtk-authored crate start point. Field (optional): `PushSpeed` (Float, default 60), the player's
+/// walking speed while pushing it.
+#[derive(Component, Clone, Debug)]
+pub struct CrateSpawn {
+    iid: String,
+    /// Centre relative to the level's bottom-left corner.
+    position: Vec2,
+    size: Vec2,
+    push_speed: f32,
+}
+
+#[derive(Bundle)]
+struct CrateSpawnBundle {
+    spawn: CrateSpawn,
+}
+
+impl LdtkEntity for CrateSpawnBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = IVec2::new(entity_instance.width, entity_instance.height);
+        let position = ldtk_pixel_coords_to_translation_pivoted(
+            entity_instance.px,
+            layer_instance.c_hei * layer_instance.grid_size,
+            size,
+            entity_instance.pivot,
+        );
+
+        Self {
+            spawn: CrateSpawn {
+                iid: entity_instance.iid.clone(),
+                position,
+                size: size.as_vec2(),
+                push_speed: entity_instance
+                    .get_float_field("PushSpeed")
+                    .copied()
+                    .unwrap_or(60.0),
+            },
+        }
+    }
+}
+
+/// IIDs of the crates spawned since the level was loaded.
+#[derive(Resource, Default)]
+struct CrateRoster {
+    spawned: HashSet<String>,
+}
+
+fn despawn_crates(
+    mut commands: Commands,
+    mut roster: ResMut<CrateRoster>,
+    query: Query<Entity, With<Crate>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    roster.spawned.clear();
+}
+
+/// Spawns a crate for every marker that hasn't produced one yet, once the level origin is known.
+fn spawn_crates(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    mut roster: ResMut<CrateRoster>,
+    spawns: Query<&CrateSpawn>,
+) {
+    let Some(origin) = level_assets.level_origin else {
+        return;
+    };
+
+    for spawn in &spawns {
+        if !roster.spawned.insert(spawn.iid.clone()) {
+            continue;
+        }
+
+        commands.spawn((
+            Name::new("Crate"),
+            Crate,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.6, 0.42, 0.25),
+                    custom_size: Some(spawn.size),
+                    ..default()
+                },
+                // Behind characters.
+                transform: Transform::from_translation((origin + spawn.position).extend(140.0)),
+                ..default()
+            },
+            Velocity::default(),
+            Collider::from_size(spawn.size),
+            Pushable {
+                push_speed: spawn.push_speed.max(1.0),
+            },
+        ));
+    }
+}
@@ -0,0 +1,716 @@
+//! Puzzle wiring. Levers, pressure plates, and timers are signal sources; `Logic` nodes combine
+//! signals with AND, OR, or toggle (flip on each rising edge); gates, platforms, and spikes react
+//! to the signal reaching them. Sources and logic nodes list what they drive in an LDtk `Targets`
+//! field (array of entity references), and a target is powered when any of its inputs is.
+//!
+//! The graph is re-evaluated every frame. Anything with memory (lever positions, toggle states,
+//! timer phases) lives in `PuzzleState`, keyed by level IID and node IID. It persists across
+//! `GameState::Loading` and derives serde traits, so a save file can store it and restore it
+//! later.
+//!
+//! Gates and platforms add their tiles to the `CollisionMap` as blockers while solid, re-applied
+//! after every rebuild the same way locked doors are. Opening one only removes its own blockers, so
+//! level geometry under a barrier stays solid.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{
+    boxes_overlap, entity_tiles, rebuild_collision_map, CollisionMap, CollisionMapChanged,
+    CollisionMapRebuilt, CollisionSystems,
+};
+use crate::health::{DamageEvent, Hurtbox};
+use crate::interact::{pick_interaction, Interact, Interactable};
+use crate::level::LevelAssets;
+use crate::movement::Collider;
+use crate::state::{GameSet, GameState};
+
+/// Registers the puzzle LDtk entities, the persistent puzzle state, and the signal systems.
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        for identifier in PUZZLE_ENTITIES {
+            app.register_ldtk_entity::<PuzzleBundle>(identifier);
+        }
+        app.register_ldtk_entity::<LeverBundle>("Lever");
+
+        app.init_resource::<PuzzleState>()
+            .add_systems(
+                PostUpdate,
+                apply_barrier_solids
+                    .after(rebuild_collision_map)
+                    .in_set(CollisionSystems),
+            )
+            .add_systems(
+                Update,
+                (
+                    operate_levers
+                        .after(pick_interaction)
+                        .in_set(GameSet::Input),
+                    (
+                        evaluate_puzzles,
+                        update_barriers,
+                        spike_damage,
+                        update_puzzle_visuals,
+                    )
+                        .chain()
+                        .in_set(GameSet::Effects),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// LDtk entity identifiers handled by `PuzzleBundle`. Levers use `LeverBundle`.
+const PUZZLE_ENTITIES: [&str; 6] = [
+    "PressurePlate",
+    "Timer",
+    "Logic",
+    "Gate",
+    "Platform",
+    "Spikes",
+];
+
+/// How close (in pixels) the player's collider must be to a lever to pull it.
+const LEVER_REACH: f32 = 4.0;
+
+/// How a `Logic` node combines its inputs. Set with the LDtk `Mode` enum field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogicMode {
+    /// Powered while every input is (and there is at least one).
+    And,
+    /// Powered while any input is.
+    #[default]
+    Or,
+    /// Flips between powered and unpowered each time its inputs go from off to on.
+    Toggle,
+}
+
+/// What a puzzle entity does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PuzzleKind {
+    /// Flipped by the player with `E`. `initial` is its position before first use.
+    Lever { initial: bool },
+    /// Powered while any collider overlaps it.
+    PressurePlate { half_extents: Vec2 },
+    /// Repeats `on_time` seconds powered, then `off_time` seconds unpowered.
+    Timer { on_time: f32, off_time: f32 },
+    Logic(LogicMode),
+    /// Blocks its tiles while `solid`. Gates are solid while unpowered and platforms while
+    /// powered; the LDtk `Inverted` bool swaps either.
+    Barrier {
+        tiles: Vec<IVec2>,
+        solid_when_powered: bool,
+        solid: bool,
+    },
+    /// Hurts whatever touches it while extended. Spikes retract while powered, or extend while
+    /// powered when `Inverted` is set.
+    Spikes {
+        half_extents: Vec2,
+        damage: i32,
+        extended_when_powered: bool,
+    },
+}
+
+/// Node in the puzzle graph. `powered` is the signal it received (targets) or produced (sources
+/// and logic) on the last evaluation.
+#[derive(Component, Clone, Debug)]
+pub struct PuzzleNode {
+    pub kind: PuzzleKind,
+    pub targets: Vec<String>,
+    pub powered: bool,
+    iid: String,
+}
+
+/// Memory for one node. Levers and toggles use `on`, toggles also `last_input`, and timers
+/// `elapsed`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeMemory {
+    pub on: bool,
+    pub last_input: bool,
+    pub elapsed: f32,
+}
+
+/// Stateful puzzle data for one level, keyed by node IID.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LevelPuzzleState {
+    pub nodes: HashMap<String, NodeMemory>,
+}
+
+/// Puzzle memory for every visited level, keyed by level IID.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleState {
+    pub levels: HashMap<String, LevelPuzzleState>,
+}
+
+#[derive(Bundle)]
+struct PuzzleBundle {
+    node: PuzzleNode,
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for PuzzleBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+        let bool_field = |identifier: &str| {
+            entity_instance
+                .get_bool_field(identifier)
+                .copied()
+                .unwrap_or(false)
+        };
+        let float_field = |identifier: &str, default: f32| {
+            entity_instance
+                .get_float_field(identifier)
+                .copied()
+                .unwrap_or(default)
+        };
+
+        let kind = match entity_instance.identifier.as_str() {
+            "Lever" => PuzzleKind::Lever {
+                initial: bool_field("On"),
+            },
+            "PressurePlate" => PuzzleKind::PressurePlate {
+                half_extents: size * 0.5,
+            },
+            "Timer" => PuzzleKind::Timer {
+                on_time: float_field("OnTime", 1.0).max(0.0),
+                off_time: float_field("OffTime", 1.0).max(0.0),
+            },
+            "Logic" => PuzzleKind::Logic(
+                match entity_instance.get_enum_field("Mode").map(String::as_str) {
+                    Ok("And") => LogicMode::And,
+                    Ok("Toggle") => LogicMode::Toggle,
+                    _ => LogicMode::Or,
+                },
+            ),
+            "Spikes" => PuzzleKind::Spikes {
+                half_extents: size * 0.5,
+                damage: entity_instance
+                    .get_int_field("Damage")
+                    .copied()
+                    .unwrap_or(1),
+                extended_when_powered: bool_field("Inverted"),
+            },
+            identifier => PuzzleKind::Barrier {
+                tiles: entity_tiles(entity_instance, layer_instance),
+                solid_when_powered: (identifier == "Platform") != bool_field("Inverted"),
+                solid: false,
+            },
+        };
+
+        let targets = entity_instance
+            .iter_entity_refs_field("Targets")
+            .map(|refs| refs.map(|r| r.entity_iid.clone()).collect())
+            .unwrap_or_default();
+
+        let (color, sprite_size) = match kind {
+            PuzzleKind::Lever { .. } => (Color::srgb(0.55, 0.5, 0.45), size * Vec2::new(0.3, 0.8)),
+            PuzzleKind::PressurePlate { .. } => {
+                (Color::srgb(0.55, 0.5, 0.45), size * Vec2::new(1.0, 0.25))
+            }
+            PuzzleKind::Barrier { .. } => (Color::srgb(0.45, 0.45, 0.55), size),
+            PuzzleKind::Spikes { .. } => (Color::srgb(0.8, 0.8, 0.85), size),
+            // Timers and logic nodes only exist in the wiring.
+            PuzzleKind::Timer { .. } | PuzzleKind::Logic(_) => (Color::NONE, size),
+        };
+
+        Self {
+            node: PuzzleNode {
+                kind,
+                targets,
+                powered: false,
+                iid: entity_instance.iid.clone(),
+            },
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(sprite_size),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// A lever is a puzzle node the player can also interact with.
+#[derive(Bundle)]
+struct LeverBundle {
+    puzzle: PuzzleBundle,
+    interactable: Interactable,
+}
+
+impl LdtkEntity for LeverBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        tileset: Option<&Handle<Image>>,
+        tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+        Self {
+            puzzle: PuzzleBundle::bundle_entity(
+                entity_instance,
+                layer_instance,
+                tileset,
+                tileset_definition,
+                asset_server,
+                texture_atlases,
+            ),
+            interactable: Interactable {
+                half_extents: size * 0.5,
+                reach: LEVER_REACH,
+            },
+        }
+    }
+}
+
+/// Flips the lever the player interacts with.
+fn operate_levers(
+    mut interactions: EventReader<Interact>,
+    level_assets: Res<LevelAssets>,
+    mut state: ResMut<PuzzleState>,
+    nodes: Query<&PuzzleNode>,
+) {
+    let Some(level_iid) = level_assets.level_iid.as_deref() else {
+        interactions.clear();
+        return;
+    };
+    let level = state.levels.entry(level_iid.to_string()).or_default();
+
+    for interaction in interactions.read() {
+        let Ok(node) = nodes.get(interaction.target) else {
+            continue;
+        };
+        let PuzzleKind::Lever { initial } = node.kind else {
+            continue;
+        };
+
+        let memory = level.nodes.entry(node.iid.clone()).or_insert(NodeMemory {
+            on: initial,
+            ..default()
+        });
+        memory.on = !memory.on;
+    }
+}
+
+/// Works out the signal of logic nodes on demand, following inputs back to the sources.
+struct Evaluator<'a> {
+    inputs: &'a HashMap<String, Vec<String>>,
+    logic: &'a HashMap<String, LogicMode>,
+    memory: &'a mut LevelPuzzleState,
+    signals: HashMap<String, bool>,
+    visiting: HashSet<String>,
+}
+
+impl Evaluator<'_> {
+    /// Whether any input of `iid` is powered. Nodes with no inputs are unpowered.
+    fn any_input(&mut self, iid: &str) -> bool {
+        self.input_signals(iid).into_iter().any(|signal| signal)
+    }
+
+    fn input_signals(&mut self, iid: &str) -> Vec<bool> {
+        let Some(inputs) = self.inputs.get(iid) else {
+            return Vec::new();
+        };
+        inputs.iter().map(|input| self.signal(input)).collect()
+    }
+
+    /// Output of a source or logic node. Targets produce no signal.
+    fn signal(&mut self, iid: &str) -> bool {
+        if let Some(signal) = self.signals.get(iid) {
+            return *signal;
+        }
+        let Some(mode) = self.logic.get(iid).copied() else {
+            return false;
+        };
+        // A node feeding back into itself sees its output from the previous frame.
+        if !self.visiting.insert(iid.to_string()) {
+            return self.memory.nodes.get(iid).is_some_and(|memory| memory.on);
+        }
+
+        let inputs = self.input_signals(iid);
+        let input = inputs.iter().any(|signal| *signal);
+        let memory = self.memory.nodes.entry(iid.to_string()).or_default();
+        let signal = match mode {
+            LogicMode::And => !inputs.is_empty() && inputs.iter().all(|signal| *signal),
+            LogicMode::Or => input,
+            LogicMode::Toggle => {
+                if input && !memory.last_input {
+                    memory.on = !memory.on;
+                }
+                memory.on
+            }
+        };
+        memory.on = signal;
+        memory.last_input = input;
+
+        self.signals.insert(iid.to_string(), signal);
+        signal
+    }
+}
+
+/// Samples the sources, then propagates signals through logic nodes to every target.
+fn evaluate_puzzles(
+    time: Res<Time>,
+    level_assets: Res<LevelAssets>,
+    mut state: ResMut<PuzzleState>,
+    mut nodes: Query<(&mut PuzzleNode, &GlobalTransform)>,
+    bodies: Query<(&Transform, &Collider)>,
+) {
+    let Some(level_iid) = level_assets.level_iid.as_deref() else {
+        return;
+    };
+    let memory = state.levels.entry(level_iid.to_string()).or_default();
+    let dt = time.delta_seconds();
+
+    let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
+    let mut logic = HashMap::new();
+    let mut signals = HashMap::new();
+
+    for (node, transform) in &nodes {
+        for target in &node.targets {
+            inputs
+                .entry(target.clone())
+                .or_default()
+                .push(node.iid.clone());
+        }
+
+        let signal = match node.kind {
+            PuzzleKind::Lever { initial, .. } => {
+                memory.nodes.get(&node.iid).map_or(initial, |lever| lever.on)
+            }
+            PuzzleKind::PressurePlate { half_extents } => {
+                let center = transform.translation().truncate();
+                bodies.iter().any(|(body, collider)| {
+                    let body_center = collider.center(body.translation.truncate());
+                    boxes_overlap(body_center, collider.half_extents, center, half_extents)
+                })
+            }
+            PuzzleKind::Timer { on_time, off_time } => {
+                let timer = memory.nodes.entry(node.iid.clone()).or_default();
+                let period = on_time + off_time;
+                if period > 0.0 {
+                    timer.elapsed = (timer.elapsed + dt) % period;
+                }
+                timer.elapsed < on_time
+            }
+            PuzzleKind::Logic(mode) => {
+                logic.insert(node.iid.clone(), mode);
+                continue;
+            }
+            PuzzleKind::Barrier { .. } | PuzzleKind::Spikes { .. } => continue,
+        };
+        signals.insert(node.iid.clone(), signal);
+    }
+
+    let mut evaluator = Evaluator {
+        inputs: &inputs,
+        logic: &logic,
+        memory,
+        signals,
+        visiting: HashSet::new(),
+    };
+
+    for (mut node, _) in &mut nodes {
+        let powered = match node.kind {
+            PuzzleKind::Barrier { .. } | PuzzleKind::Spikes { .. } => {
+                evaluator.any_input(&node.iid)
+            }
+            _ => evaluator.signal(&node.iid),
+        };
+        if node.powered != powered {
+            node.powered = powered;
+        }
+    }
+}
+
+/// Opens and closes gates and platforms to match their signal. A barrier won't close on top of a
+/// collider; it waits until the space is clear.
+fn update_barriers(
+    mut map: ResMut<CollisionMap>,
+    mut changed: EventWriter<CollisionMapChanged>,
+    mut nodes: Query<&mut PuzzleNode>,
+    bodies: Query<(&Transform, &Collider)>,
+) {
+    for mut node in &mut nodes {
+        let PuzzleKind::Barrier {
+            ref tiles,
+            solid_when_powered,
+            solid,
+        } = node.kind
+        else {
+            continue;
+        };
+        let want_solid = node.powered == solid_when_powered;
+        if want_solid == solid {
+            continue;
+        }
+
+        let tiles: Vec<IVec2> = if want_solid {
+            let blocked = bodies.iter().any(|(transform, collider)| {
+                let center = collider.center(transform.translation.truncate());
+                tiles.iter().any(|tile| {
+                    let rect = map.tile_to_world_rect(*tile);
+                    boxes_overlap(center, collider.half_extents, rect.center(), rect.half_size())
+                })
+            });
+            if blocked {
+                continue;
+            }
+            tiles
+                .iter()
+                .copied()
+                .filter(|tile| map.add_blocker(*tile))
+                .collect()
+        } else {
+            tiles
+                .iter()
+                .copied()
+                .filter(|tile| map.remove_blocker(*tile))
+                .collect()
+        };
+        if !tiles.is_empty() {
+            changed.send(CollisionMapChanged { tiles });
+        }
+
+        if let PuzzleKind::Barrier { solid, .. } = &mut node.kind {
+            *solid = want_solid;
+        }
+    }
+}
+
+/// Re-applies solid barriers to the freshly rebuilt collision map.
+fn apply_barrier_solids(
+    mut rebuilt: EventReader<CollisionMapRebuilt>,
+    nodes: Query<&PuzzleNode>,
+    mut map: ResMut<CollisionMap>,
+    mut changed: EventWriter<CollisionMapChanged>,
+) {
+    if rebuilt.read().count() == 0 {
+        return;
+    }
+
+    let mut added = Vec::new();
+    for node in &nodes {
+        if let PuzzleKind::Barrier {
+            tiles, solid: true, ..
+        } = &node.kind
+        {
+            added.extend(tiles.iter().filter(|tile| map.add_blocker(**tile)));
+        }
+    }
+
+    if !added.is_empty() {
+        changed.send(CollisionMapChanged { tiles: added });
+    }
+}
+
+/// Damages hurtboxes touching extended spikes.
+fn spike_damage(
+    nodes: Query<(&PuzzleNode, &GlobalTransform)>,
+    targets: Query<(Entity, &Transform, &Hurtbox)>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (node, transform) in &nodes {
+        let PuzzleKind::Spikes {
+            half_extents,
+            damage: amount,
+            extended_when_powered,
+        } = node.kind
+        else {
+            continue;
+        };
+        if node.powered != extended_when_powered {
+            continue;
+        }
+
+        let center = transform.translation().truncate();
+        for (target, target_transform, hurtbox) in &targets {
+            if hurtbox.overlaps(target_transform.translation.truncate(), center, half_extents) {
+                damage.send(DamageEvent {
+                    target,
+                    amount,
+                    source: Some(center),
+                    knockback: None,
+                });
+            }
+        }
+    }
+}
+
+/// Lights up active levers and plates, and hides open barriers and retracted spikes.
+fn update_puzzle_visuals(
+    mut nodes: Query<(&PuzzleNode, &mut Sprite, &mut Visibility), Changed<PuzzleNode>>,
+) {
+    for (node, mut sprite, mut visibility) in &mut nodes {
+        let shown = match node.kind {
+            PuzzleKind::Lever { .. } | PuzzleKind::PressurePlate { .. } => {
+                sprite.color = if node.powered {
+                    Color::srgb(0.4, 0.9, 0.5)
+                } else {
+                    Color::srgb(0.55, 0.5, 0.45)
+                };
+                true
+            }
+            PuzzleKind::Barrier { solid, .. } => solid,
+            PuzzleKind::Spikes {
+                extended_when_powered,
+                ..
+            } => node.powered == extended_when_powered,
+            PuzzleKind::Timer { .. } | PuzzleKind::Logic(_) => continue,
+        };
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates `iid` for one frame. `wires` are `(from, to)` pairs and `sources` the signals
+    /// sampled from levers, plates, and timers this frame.
+    fn evaluate(
+        wires: &[(&str, &str)],
+        logic: &[(&str, LogicMode)],
+        sources: &[(&str, bool)],
+        memory: &mut LevelPuzzleState,
+        iid: &str,
+    ) -> bool {
+        let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
+        for (from, to) in wires {
+            inputs
+                .entry(to.to_string())
+                .or_default()
+                .push(from.to_string());
+        }
+        let logic = logic
+            .iter()
+            .map(|(iid, mode)| (iid.to_string(), *mode))
+            .collect();
+
+        Evaluator {
+            inputs: &inputs,
+            logic: &logic,
+            memory,
+            signals: sources
+                .iter()
+                .map(|(iid, signal)| (iid.to_string(), *signal))
+                .collect(),
+            visiting: HashSet::new(),
+        }
+        .signal(iid)
+    }
+
+    #[test]
+    fn and_needs_every_input_and_or_any() {
+        let wires = [("a", "and"), ("b", "and"), ("a", "or"), ("b", "or")];
+        let logic = [("and", LogicMode::And), ("or", LogicMode::Or)];
+        let mut memory = LevelPuzzleState::default();
+
+        for (a, b) in [(false, false), (true, false), (false, true), (true, true)] {
+            let sources = [("a", a), ("b", b)];
+            assert_eq!(
+                evaluate(&wires, &logic, &sources, &mut memory, "and"),
+                a && b
+            );
+            assert_eq!(
+                evaluate(&wires, &logic, &sources, &mut memory, "or"),
+                a || b
+            );
+        }
+        // An AND gate with nothing wired to it stays off.
+        assert!(!evaluate(&[], &logic, &[], &mut memory, "and"));
+    }
+
+    #[test]
+    fn toggle_flips_on_rising_edges_only() {
+        let wires = [("plate", "toggle")];
+        let logic = [("toggle", LogicMode::Toggle)];
+        let mut memory = LevelPuzzleState::default();
+
+        let frames = [
+            (false, false),
+            (true, true),
+            (true, true),
+            (false, true),
+            (true, false),
+            (false, false),
+        ];
+        for (plate, expected) in frames {
+            let sources = [("plate", plate)];
+            assert_eq!(
+                evaluate(&wires, &logic, &sources, &mut memory, "toggle"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn feedback_loops_read_the_previous_frame() {
+        // An OR gate wired back into itself latches once its other input fires.
+        let wires = [("button", "latch"), ("latch", "latch")];
+        let logic = [("latch", LogicMode::Or)];
+        let mut memory = LevelPuzzleState::default();
+        let mut latch =
+            |button: bool| evaluate(&wires, &logic, &[("button", button)], &mut memory, "latch");
+
+        assert!(!latch(false));
+        assert!(latch(true));
+        assert!(latch(false));
+
+        // Two gates feeding each other with nothing else powering them settle off.
+        let wires = [("x", "y"), ("y", "x")];
+        let logic = [("x", LogicMode::Or), ("y", LogicMode::Or)];
+        assert!(!evaluate(&wires, &logic, &[], &mut memory, "x"));
+    }
+
+    #[test]
+    fn puzzle_state_survives_a_ron_round_trip() {
+        let mut state = PuzzleState::default();
+        let level = state.levels.entry("level".to_string()).or_default();
+        level.nodes.insert(
+            "lever".to_string(),
+            NodeMemory {
+                on: true,
+                ..default()
+            },
+        );
+        level.nodes.insert(
+            "timer".to_string(),
+            NodeMemory {
+                elapsed: 0.75,
+                ..default()
+            },
+        );
+        level.nodes.insert(
+            "toggle".to_string(),
+            NodeMemory {
+                on: true,
+                last_input: true,
+                ..default()
+            },
+        );
+
+        let saved = ron::to_string(&state).unwrap();
+        let restored: PuzzleState = ron::from_str(&saved).unwrap();
+        assert_eq!(restored, state);
+    }
+}
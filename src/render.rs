@@ -0,0 +1,196 @@
+//! Pixel-perfect presentation. In this mode the game camera renders into a small off-screen canvas
+//! (one texel per LDtk pixel), and a second camera draws that canvas to the window at the largest
+//! whole-number scale that fits, letterboxing whatever is left over. Combined with the camera
+//! controller snapping to texel boundaries, tiles no longer shimmer while scrolling.
+//!
+//! The canvas image is owned by Bevy's asset storage; swapping render targets only changes handles,
+//! so toggling the mode at runtime never reallocates anything but the canvas itself.
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use bevy::window::{PrimaryWindow, WindowRef};
+
+use crate::camera::FollowCamera;
+
+/// Registers the canvas, the presentation camera, and the systems that keep them in sync with
+/// `RenderSettings` and the window size.
+pub struct PixelPerfectPlugin;
+
+impl Plugin for PixelPerfectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderSettings>()
+            .add_systems(Startup, setup_canvas)
+            .add_systems(PostUpdate, apply_render_settings);
+    }
+}
+
+/// Render layer used only by the canvas sprite and the presentation camera, so the game camera
+/// never draws the canvas into itself.
+const PRESENT_LAYER: usize = 1;
+
+/// Display options. `pixel_perfect` switches between the integer-scaled canvas and drawing straight
+/// to the window; `canvas_size` is the low-resolution view in texels (and world units).
+#[derive(Resource, Clone)]
+pub struct RenderSettings {
+    pub pixel_perfect: bool,
+    pub canvas_size: UVec2,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            pixel_perfect: false,
+            canvas_size: UVec2::new(640, 360),
+        }
+    }
+}
+
+/// Handle to the off-screen image the game camera renders into in pixel-perfect mode.
+#[derive(Resource)]
+struct PixelCanvas(Handle<Image>);
+
+/// Camera that draws the canvas sprite to the window.
+#[derive(Component)]
+struct PresentCamera;
+
+/// Sprite showing the canvas image, scaled up by a whole number.
+#[derive(Component)]
+struct CanvasSprite;
+
+/// Creates the canvas image along with the presentation camera and sprite. Both start inactive
+/// (or invisible); `apply_render_settings` switches them on when pixel-perfect mode is selected.
+fn setup_canvas(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<RenderSettings>,
+) {
+    let size = canvas_extent(settings.canvas_size);
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("pixel_canvas"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let handle = images.add(image);
+
+    commands.spawn((
+        PresentCamera,
+        Name::new("PresentCamera"),
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                is_active: false,
+                // Letterbox bars around the scaled canvas.
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::layer(PRESENT_LAYER),
+    ));
+
+    commands.spawn((
+        CanvasSprite,
+        Name::new("PixelCanvas"),
+        SpriteBundle {
+            texture: handle.clone(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        RenderLayers::layer(PRESENT_LAYER),
+    ));
+
+    commands.insert_resource(PixelCanvas(handle));
+}
+
+fn canvas_extent(size: UVec2) -> Extent3d {
+    Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    }
+}
+
+/// Largest whole-number scale at which `canvas` still fits inside `window` (both in physical
+/// pixels). Never less than one, so tiny windows crop rather than show nothing.
+fn integer_scale(window: Vec2, canvas: UVec2) -> f32 {
+    let fit = window / canvas.max(UVec2::ONE).as_vec2();
+    fit.min_element().floor().max(1.0)
+}
+
+/// Points the game camera at the canvas or the window, toggles the presentation camera, and keeps
+/// the canvas sprite at an integer scale. Only does work when the settings or the window change.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_render_settings(
+    mut commands: Commands,
+    settings: Res<RenderSettings>,
+    canvas: Option<Res<PixelCanvas>>,
+    mut images: ResMut<Assets<Image>>,
+    windows: Query<Ref<Window>, With<PrimaryWindow>>,
+    mut game_camera: Query<(Entity, &mut Camera), (With<FollowCamera>, Without<PresentCamera>)>,
+    mut present_camera: Query<(Entity, &mut Camera), With<PresentCamera>>,
+    mut sprite: Query<(&mut Transform, &mut Visibility), With<CanvasSprite>>,
+) {
+    let (Some(canvas), Ok(window)) = (canvas, windows.get_single()) else {
+        return;
+    };
+    if !settings.is_changed() && !window.is_changed() && !canvas.is_added() {
+        return;
+    }
+    let (Ok((game_entity, mut game)), Ok((present_entity, mut present))) =
+        (game_camera.get_single_mut(), present_camera.get_single_mut())
+    else {
+        return;
+    };
+
+    if let Some(image) = images.get_mut(&canvas.0) {
+        let size = canvas_extent(settings.canvas_size);
+        if image.texture_descriptor.size != size {
+            image.resize(size);
+        }
+    }
+
+    game.target = if settings.pixel_perfect {
+        RenderTarget::Image(canvas.0.clone())
+    } else {
+        RenderTarget::Window(WindowRef::Primary)
+    };
+    present.is_active = settings.pixel_perfect;
+
+    // UI is drawn by whichever camera renders to the window, so it stays at full resolution.
+    let (ui_camera, other) = if settings.pixel_perfect {
+        (present_entity, game_entity)
+    } else {
+        (game_entity, present_entity)
+    };
+    commands.entity(ui_camera).insert(IsDefaultUiCamera);
+    commands.entity(other).remove::<IsDefaultUiCamera>();
+
+    if let Ok((mut transform, mut visibility)) = sprite.get_single_mut() {
+        let physical = Vec2::new(
+            window.resolution.physical_width() as f32,
+            window.resolution.physical_height() as f32,
+        );
+        let scale = integer_scale(physical, settings.canvas_size) / window.scale_factor();
+        transform.scale = Vec3::new(scale, scale, 1.0);
+        *visibility = if settings.pixel_perfect {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -12,6 +12,11 @@ pub enum GameState {
     Loading,
     Playing,
     Paused,
+    /// Entered when an LDtk project cannot be loaded and there is no earlier level to fall back to;
+    /// the error screen offers retry/menu options.
+    LoadError,
+    /// Minimal title screen reachable from the load error screen.
+    Menu,
 }
 
 /// Named system sets to structure the Update schedule.
@@ -36,6 +41,6 @@ pub fn toggle_pause(
     match state.get() {
         GameState::Playing => next_state.set(GameState::Paused),
         GameState::Paused => next_state.set(GameState::Playing),
-        GameState::Loading => {}
+        GameState::Loading | GameState::LoadError | GameState::Menu => {}
     }
 }
@@ -1,11 +1,12 @@
 //! Level transition system with fade effects. Detects when the player touches special trigger tiles
-//! (IntGrid value 2) and smoothly transitions to the next level with a black screen fade.
+//! (IntGrid value 2) and smoothly transitions to the next level with a black screen fade. Where each
+//! exit leads is defined by the `next` field of the current level in the campaign manifest.
 
-use bevy::math::IVec2;
 use bevy::prelude::*;
 
+use crate::campaign::{Campaign, CampaignProgress};
 use crate::collision::CollisionMap;
-use crate::level::{LevelAssets, LevelConfig};
+use crate::level::LevelConfig;
 use crate::player::Player;
 use crate::state::{GameSet, GameState};
 
@@ -15,7 +16,6 @@ pub struct TransitionPlugin;
 impl Plugin for TransitionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TransitionState>()
-            .init_resource::<SpawnPositions>()
             .add_systems(
                 Update,
                 (
@@ -25,6 +25,7 @@ impl Plugin for TransitionPlugin {
                     .run_if(in_state(GameState::Playing)),
             )
             .add_systems(OnEnter(GameState::Playing), spawn_fade_overlay)
+            .add_systems(OnEnter(GameState::LoadError), cancel_transition)
             .add_systems(Update, update_fade_overlay);
     }
 }
@@ -35,24 +36,24 @@ pub struct TransitionState {
     pub is_transitioning: bool,
     pub fade_timer: f32,
     pub fade_duration: f32,
-    pub next_level_path: Option<String>,
-    pub next_level_name: Option<String>,
+    /// Campaign manifest id of the level to load at the fade midpoint.
+    pub next_level: Option<String>,
+    /// Whether the player overlapped an exit tile last frame, so exits only fire on entry.
+    pub touching_exit: bool,
 }
 
 impl TransitionState {
-    pub fn start_transition(&mut self, level_path: String, level_name: String) {
+    pub fn start_transition(&mut self, level_id: String) {
         self.is_transitioning = true;
         self.fade_timer = 0.0;
         self.fade_duration = 1.0; // Total fade time (0.5 out + 0.5 in)
-        self.next_level_path = Some(level_path);
-        self.next_level_name = Some(level_name);
+        self.next_level = Some(level_id);
     }
 
     pub fn reset(&mut self) {
         self.is_transitioning = false;
         self.fade_timer = 0.0;
-        self.next_level_path = None;
-        self.next_level_name = None;
+        self.next_level = None;
     }
 
     /// Returns the current fade alpha (0.0 = transparent, 1.0 = fully black)
@@ -72,30 +73,6 @@ impl TransitionState {
     }
 }
 
-/// Stores spawn positions for each level by project path.
-#[derive(Resource)]
-pub struct SpawnPositions {
-    positions: std::collections::HashMap<String, Vec2>,
-}
-
-impl Default for SpawnPositions {
-    fn default() -> Self {
-        let mut positions = std::collections::HashMap::new();
-        positions.insert("levels/test_map_1_newres.ldtk".to_owned(), Vec2::new(340.0, 340.0));
-        positions.insert("levels/level_2.ldtk".to_owned(), Vec2::new(57.0, 552.0));
-        Self { positions }
-    }
-}
-
-impl SpawnPositions {
-    pub fn get(&self, project_path: &str) -> Vec2 {
-        self.positions
-            .get(project_path)
-            .copied()
-            .unwrap_or(Vec2::new(340.0, 340.0))
-    }
-}
-
 /// Marker component for the fade overlay sprite.
 #[derive(Component)]
 pub struct FadeOverlay;
@@ -129,12 +106,21 @@ fn update_fade_overlay(
     }
 }
 
-/// Checks if the player is touching a trigger tile (value 2) and initiates level transition.
+/// Clears any in-flight fade when a load fails so the overlay doesn't stay black behind the error
+/// screen.
+fn cancel_transition(mut transition: ResMut<TransitionState>) {
+    transition.reset();
+}
+
+/// Checks if the player has just stepped onto a trigger tile (value 2) and initiates a transition to
+/// the current campaign level's `next` level, provided its unlock requirements are met.
 fn check_level_triggers(
     player_query: Query<(&Transform, &crate::movement::Collider), With<Player>>,
     collision_map: Res<CollisionMap>,
     mut transition: ResMut<TransitionState>,
-    level_assets: Res<LevelAssets>,
+    level_config: Res<LevelConfig>,
+    campaign: Campaign,
+    mut progress: ResMut<CampaignProgress>,
     keyboard: Res<ButtonInput<KeyCode>>,
 ) {
     if transition.is_transitioning {
@@ -145,7 +131,7 @@ fn check_level_triggers(
         return;
     };
 
-    let position = transform.translation.truncate();
+    let position = collider.center(transform.translation.truncate());
     let half_size = collider.half_extents;
 
     // Debug: Press 'T' to print collision map info
@@ -174,9 +160,7 @@ fn check_level_triggers(
             Vec2::new(half_size.x, half_size.y),
         ].iter().enumerate() {
             let check_pos = position + *offset;
-            let tile_x = ((check_pos.x - collision_map.origin.x) / collision_map.tile_size.x).floor() as i32;
-            let tile_y = ((check_pos.y - collision_map.origin.y) / collision_map.tile_size.y).floor() as i32;
-            let tile = IVec2::new(tile_x, tile_y);
+            let tile = collision_map.world_to_tile(check_pos);
             let value = collision_map.get_tile_value(tile);
             info!("  Check point {}: world_pos={:?}, tile={:?}, value={:?}", i, check_pos, tile, value);
         }
@@ -195,26 +179,50 @@ fn check_level_triggers(
         Vec2::new(half_size.x, 0.0),           // Right-center
     ];
 
-    for offset in &offsets {
+    let exit_tile = offsets.iter().find_map(|offset| {
         let check_pos = position + *offset;
-        let tile_x = ((check_pos.x - collision_map.origin.x) / collision_map.tile_size.x).floor() as i32;
-        let tile_y = ((check_pos.y - collision_map.origin.y) / collision_map.tile_size.y).floor() as i32;
-        let tile = IVec2::new(tile_x, tile_y);
-
-        if let Some(value) = collision_map.get_tile_value(tile) {
-            if value == 2 {
-                info!("Detected value 2 tile at {:?}, triggering transition!", tile);
-                // Trigger transition to second level
-                let current_path = level_assets.project_path.as_deref().unwrap_or("levels/test_map_1_newres.ldtk");
-                if current_path == "levels/test_map_1_newres.ldtk" {
-                    info!("Starting transition from first level to second level");
-                    transition.start_transition(
-                        "levels/level_2.ldtk".to_owned(),
-                        "Level_0".to_owned(),
-                    );
-                }
-                return;
-            }
+        let tile = collision_map.world_to_tile(check_pos);
+        (collision_map.get_tile_value(tile) == Some(2)).then_some(tile)
+    });
+
+    // Only the frame the player steps onto an exit counts, so standing on a locked exit doesn't log
+    // or complete the level again every frame.
+    let was_touching = std::mem::replace(&mut transition.touching_exit, exit_tile.is_some());
+    let Some(tile) = exit_tile else {
+        return;
+    };
+    if was_touching {
+        return;
+    }
+
+    info!("Detected value 2 tile at {:?}, triggering transition!", tile);
+    let Some(manifest) = campaign.manifest() else {
+        warn!("No campaign manifest loaded; level exits are disabled");
+        return;
+    };
+    let Some(current) = level_config.campaign_level(manifest) else {
+        return;
+    };
+    let Some(next_id) = current.next.as_deref() else {
+        return;
+    };
+
+    // Leaving through the exit completes the level, which may unlock the next one.
+    progress.completed.insert(current.id.clone());
+
+    match manifest.level(next_id) {
+        Some(next) if next.is_unlocked(&progress) => {
+            info!(
+                "Starting transition from '{}' to '{}'",
+                current.display_name, next.display_name
+            );
+            transition.start_transition(next.id.clone());
+        }
+        Some(next) => {
+            info!("'{}' is locked; requires {:?}", next.display_name, next.requires);
+        }
+        None => {
+            warn!("Campaign level '{}' leads to unknown level '{}'", current.id, next_id);
         }
     }
 }
@@ -225,6 +233,7 @@ fn update_transition(
     mut transition: ResMut<TransitionState>,
     mut next_state: ResMut<NextState<GameState>>,
     mut level_config: ResMut<LevelConfig>,
+    campaign: Campaign,
 ) {
     if !transition.is_transitioning {
         return;
@@ -235,9 +244,13 @@ fn update_transition(
     // Switch level at the midpoint when screen is fully black
     let half_duration = transition.fade_duration * 0.5;
     if transition.fade_timer >= half_duration && transition.fade_timer - time.delta_seconds() < half_duration {
-        if let (Some(path), Some(name)) = (transition.next_level_path.take(), transition.next_level_name.take()) {
-            level_config.project_path = path;
-            level_config.start_level = Some(name);
+        let next_level = transition
+            .next_level
+            .take()
+            .and_then(|id| campaign.manifest()?.level(&id));
+        if let Some(level) = next_level {
+            level_config.select_campaign_level(level);
+            level_config.entry = None;
             next_state.set(GameState::Loading);
         }
     }
@@ -1,25 +1,173 @@
-//! Pause menu UI. Spawns a simple overlay when the game enters the `Paused` state.
+//! Pause menu, load error screen, and title menu UI. Each overlay is spawned on entering its
+//! `GameState` and removed again on exit. The gameplay HUD (the collectible tally and a short
+//! notice for each pickup) stays up through pauses and is only removed when a level loads. A
+//! failed load that returned to the previous level is reported with a short-lived notice instead
+//! of the error screen.
 //!
 //! UI entities are part of Bevy's ECS; once despawned, all associated style/text components are
 //! dropped automatically.
 
 use bevy::prelude::*;
 
-use crate::state::GameState;
+use crate::collectible::{CollectibleKind, CollectibleTally, ItemCollected, KeyColor};
+use crate::level::{LevelConfig, LevelHistory, LevelLoadFailure};
+use crate::state::{GameSet, GameState};
 
-/// Registers pause overlay spawn/despawn systems.
+/// Registers the HUD plus pause overlay, load error screen, and title menu spawn/despawn systems.
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
-            .add_systems(OnExit(GameState::Paused), despawn_pause_menu);
+        app.add_systems(OnEnter(GameState::Playing), spawn_hud)
+            .add_systems(OnEnter(GameState::Loading), despawn_hud)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_pause_menu)
+            .add_systems(OnEnter(GameState::LoadError), spawn_load_error_screen)
+            .add_systems(OnExit(GameState::LoadError), despawn_load_error_screen)
+            .add_systems(OnEnter(GameState::Menu), spawn_main_menu)
+            .add_systems(OnExit(GameState::Menu), despawn_main_menu)
+            .add_systems(OnEnter(GameState::Playing), spawn_load_failure_notice)
+            .add_systems(
+                Update,
+                (
+                    update_hud
+                        .after(GameSet::Effects)
+                        .run_if(in_state(GameState::Playing)),
+                    handle_load_error_input.run_if(in_state(GameState::LoadError)),
+                    handle_main_menu_input.run_if(in_state(GameState::Menu)),
+                    expire_load_failure_notice,
+                ),
+            );
     }
 }
 
+/// Seconds the pickup notice stays on screen, fading out over the last half.
+const PICKUP_NOTICE_SECONDS: f32 = 1.5;
+
+/// How long the notice about a rolled-back level load stays on screen, in seconds.
+const LOAD_FAILURE_NOTICE_SECONDS: f32 = 6.0;
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct TallyText;
+
+/// The "+1 Gem" line under the tally, with the seconds it has left.
+#[derive(Component, Default)]
+struct PickupNotice {
+    remaining: f32,
+}
+
 #[derive(Component)]
 struct PauseMenu;
 
+#[derive(Component)]
+struct LoadErrorScreen;
+
+#[derive(Component)]
+struct MainMenu;
+
+/// Banner naming a level that failed to load after the game fell back to the previous one.
+#[derive(Component)]
+struct LoadFailureNotice(Timer);
+
+fn tally_text(tally: &CollectibleTally) -> String {
+    let mut text = format!(
+        "Coins {}   Gems {}",
+        tally.count(CollectibleKind::Coin),
+        tally.count(CollectibleKind::Gem)
+    );
+    // Keys only show up while the player is carrying some.
+    for kind in KeyColor::ALL.map(CollectibleKind::Key) {
+        let count = tally.count(kind);
+        if count > 0 {
+            text.push_str(&format!("   {} {}", kind.name(count), count));
+        }
+    }
+    text
+}
+
+/// Spawns the HUD in the top-left corner the first time gameplay starts after a level load.
+/// Unpausing enters `Playing` again, so an existing HUD is left alone.
+fn spawn_hud(mut commands: Commands, tally: Res<CollectibleTally>, hud: Query<(), With<Hud>>) {
+    if !hud.is_empty() {
+        return;
+    }
+
+    let style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::srgba(0.95, 0.95, 0.85, 1.0),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Hud,
+            Name::new("Hud"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(12.0),
+                    top: Val::Px(8.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TallyText,
+                TextBundle::from_section(tally_text(&tally), style(20.0)),
+            ));
+            parent.spawn((
+                PickupNotice::default(),
+                TextBundle::from_section("", style(16.0)),
+            ));
+        });
+}
+
+fn despawn_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Refreshes the tally whenever it changes, names the latest pickup for a moment, and fades that
+/// notice out again.
+fn update_hud(
+    time: Res<Time>,
+    tally: Res<CollectibleTally>,
+    mut collected: EventReader<ItemCollected>,
+    mut tally_text_query: Query<&mut Text, (With<TallyText>, Without<PickupNotice>)>,
+    mut notice_query: Query<(&mut Text, &mut PickupNotice), Without<TallyText>>,
+) {
+    // Doors take keys back out of the tally, so refresh on any change rather than on pickups.
+    if tally.is_changed() {
+        for mut text in &mut tally_text_query {
+            text.sections[0].value = tally_text(&tally);
+        }
+    }
+
+    let latest = collected.read().last().copied();
+    let Ok((mut notice_text, mut notice)) = notice_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(ItemCollected { kind, value }) = latest {
+        notice_text.sections[0].value = format!("+{} {}", value, kind.name(value));
+        notice.remaining = PICKUP_NOTICE_SECONDS;
+    } else if notice.remaining <= 0.0 {
+        return;
+    }
+
+    notice.remaining = (notice.remaining - time.delta_seconds()).max(0.0);
+    let alpha = (notice.remaining / (PICKUP_NOTICE_SECONDS * 0.5)).min(1.0);
+    notice_text.sections[0].style.color.set_alpha(alpha);
+}
+
 /// Spawns a full-screen UI node with centered text. Nodes live in the `Ui` world and are rendered
 /// by the UI camera automatically.
 fn spawn_pause_menu(mut commands: Commands) {
@@ -57,3 +205,205 @@ fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMen
         commands.entity(entity).despawn_recursive();
     }
 }
+
+/// Shows which project failed to load and why, along with the available recovery keys.
+fn spawn_load_error_screen(mut commands: Commands, failure: Res<LevelLoadFailure>) {
+    commands
+        .spawn((
+            LoadErrorScreen,
+            Name::new("LoadErrorScreen"),
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgba(0.1, 0.0, 0.0, 0.9)),
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Failed to load level '{}'", failure.path()),
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::srgba(1.0, 0.6, 0.6, 1.0),
+                    ..default()
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    failure.error.clone(),
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::srgba(0.9, 0.9, 0.9, 1.0),
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    max_width: Val::Percent(80.0),
+                    ..default()
+                }),
+            );
+            parent.spawn(TextBundle::from_section(
+                "[R] Retry    [M] Main menu",
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::srgba(0.9, 0.9, 0.9, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Removes the load error screen on state exit.
+fn despawn_load_error_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<LoadErrorScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Retry re-applies the failed configuration; the menu option leaves level loading entirely. There
+/// is no "back" option because failed transitions return to the previous level on their own.
+fn handle_load_error_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    failure: Res<LevelLoadFailure>,
+    mut config: ResMut<LevelConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        if let Some(attempted) = failure.attempted.clone() {
+            *config = attempted;
+        }
+        next_state.set(GameState::Loading);
+    } else if keyboard.just_pressed(KeyCode::KeyM) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+/// Once the previous level is back after a failed load, names the project that failed and why.
+fn spawn_load_failure_notice(mut commands: Commands, mut failure: ResMut<LevelLoadFailure>) {
+    if !failure.rolled_back {
+        return;
+    }
+    failure.rolled_back = false;
+
+    commands
+        .spawn((
+            LoadFailureNotice(Timer::from_seconds(
+                LOAD_FAILURE_NOTICE_SECONDS,
+                TimerMode::Once,
+            )),
+            Name::new("LoadFailureNotice"),
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgba(0.1, 0.0, 0.0, 0.8)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.0),
+                    left: Val::Percent(10.0),
+                    width: Val::Percent(80.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Failed to load level '{}'; returned to the previous level",
+                    failure.path()
+                ),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::srgba(1.0, 0.6, 0.6, 1.0),
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                failure.error.clone(),
+                TextStyle {
+                    font_size: 14.0,
+                    color: Color::srgba(0.9, 0.9, 0.9, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Removes the rollback notice once its timer runs out.
+fn expire_load_failure_notice(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notices: Query<(Entity, &mut LoadFailureNotice)>,
+) {
+    for (entity, mut notice) in &mut notices {
+        if notice.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawns the title screen. It is intentionally bare until proper menus are designed.
+fn spawn_main_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            MainMenu,
+            Name::new("MainMenu"),
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 1.0)),
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Dungeon Platformer\nPress Enter to start",
+                TextStyle {
+                    font_size: 36.0,
+                    color: Color::srgba(0.9, 0.9, 0.9, 1.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Removes the title screen on state exit.
+fn despawn_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenu>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Starts a fresh run from the configuration the session originally booted with.
+fn handle_main_menu_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    history: Res<LevelHistory>,
+    mut config: ResMut<LevelConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    if let Some(start) = history.start.clone() {
+        *config = start;
+    }
+    next_state.set(GameState::Loading);
+}